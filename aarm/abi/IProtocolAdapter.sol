@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+// The interface of the protocol adapter as encoded by `aarm::evm`. The Rust
+// types are generated from this file, so a change here changes the calldata
+// and must be matched by the deployed adapter.
+
+struct Transaction {
+    Action[] actions;
+    bytes deltaProof;
+}
+
+struct Action {
+    LogicProof[] logicProofs;
+    ComplianceUnit[] complianceUnits;
+    ResourceForwarderCalldataPair[] resourceCalldataPairs;
+}
+
+struct LogicProof {
+    bytes proof;
+    LogicInstance instance;
+    bytes32 logicRef;
+}
+
+struct LogicInstance {
+    bytes32 tag;
+    bool isConsumed;
+    bytes32 actionTreeRoot;
+    uint32 actionTreeDepth;
+    bytes ciphertext;
+    ExpirableBlob[] appData;
+}
+
+struct ExpirableBlob {
+    uint8 deletionCriterion;
+    bytes blob;
+}
+
+struct ComplianceUnit {
+    bytes proof;
+    ComplianceInstance instance;
+}
+
+struct ComplianceInstance {
+    ConsumedRefs consumed;
+    CreatedRefs created;
+    bytes32 unitDeltaX;
+    bytes32 unitDeltaY;
+}
+
+struct ConsumedRefs {
+    bytes32 nullifier;
+    bytes32 logicRef;
+    bytes32 commitmentTreeRoot;
+}
+
+struct CreatedRefs {
+    bytes32 commitment;
+    bytes32 logicRef;
+}
+
+struct ResourceForwarderCalldataPair {
+    Resource carrier;
+    ForwarderCalldata call;
+}
+
+struct Resource {
+    bytes32 logicRef;
+    bytes32 labelRef;
+    bytes32 valueRef;
+    bytes32 nullifierKeyCommitment;
+    uint256 quantity;
+    uint256 nonce;
+    uint256 randSeed;
+    bool ephemeral;
+}
+
+struct ForwarderCalldata {
+    address untrustedForwarder;
+    bytes input;
+    bytes output;
+}
+
+interface IProtocolAdapter {
+    function execute(Transaction calldata transaction) external;
+}
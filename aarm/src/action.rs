@@ -1,25 +1,36 @@
 use crate::utils::groth16_prove;
 use crate::{
-    constants::{COMPLIANCE_GUEST_ELF, COMPLIANCE_GUEST_ID, TEST_GUEST_ELF, TEST_GUEST_ID},
+    constants::{
+        COMPLIANCE_GUEST_ELF, COMPLIANCE_GUEST_ID, MULTI_COMPLIANCE_GUEST_ID, TEST_GUEST_ELF,
+        TEST_GUEST_ID,
+    },
+    error::{ProvingError, VerificationError},
     logic_proof::LogicProof,
     utils::verify as verify_proof,
 };
-use aarm_core::compliance::ComplianceWitness;
+use aarm_core::aggregation::ActionJournals;
+use aarm_core::compliance::{ComplianceWitness, MultiComplianceInstance};
 use aarm_core::delta_proof::DeltaWitness;
 use aarm_core::nullifier_key::NullifierKey;
 use aarm_core::resource::Resource;
 use aarm_core::resource_logic::TrivialLogicWitness;
 use aarm_core::{
-    action_tree::MerkleTree, compliance::ComplianceInstance, constants::COMMITMENT_TREE_DEPTH,
-    logic_instance::LogicInstance,
+    action_tree::{MerkleTree, ACTION_TREE_DEPTH},
+    compliance::ComplianceInstance,
+    constants::COMMITMENT_TREE_DEPTH,
+    logic_instance::{forwarder_call_binding, ExpirableBlob, LogicInstance},
 };
 use k256::ProjectivePoint;
 use risc0_zkvm::{Digest, Receipt};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Action {
     pub compliance_units: Vec<Receipt>,
+    // Units with any number of consumed and created resources
+    #[serde(default)]
+    pub multi_compliance_units: Vec<Receipt>,
     pub logic_proofs: Vec<LogicProof>,
     pub resource_forwarder_calldata_pairs: Vec<(Resource, ForwarderCalldata)>,
 }
@@ -31,6 +42,96 @@ pub struct ForwarderCalldata {
     pub output: Vec<u8>,
 }
 
+impl ForwarderCalldata {
+    /// The app data entry that the logic of `resource` must output, in the
+    /// logic proof of `tag`, to authorize this call, see
+    /// `Action::verify_forwarder_calls`.
+    pub fn binding(&self, tag: &Digest, resource: &Resource) -> ExpirableBlob {
+        forwarder_call_binding(
+            tag,
+            &resource.commitment(),
+            &self.untrusted_forwarder,
+            &self.input,
+            &self.output,
+        )
+    }
+}
+
+/// Indexes the tags of an action tree by position. Tags must be unique: a
+/// repeated tag would leave one of its leaves without a logic proof.
+pub(crate) fn tag_positions(
+    tags: &[Digest],
+    is_consumed: impl Fn(usize) -> bool,
+) -> Result<HashMap<Digest, usize>, VerificationError> {
+    let mut positions = HashMap::with_capacity(tags.len());
+    for (position, tag) in tags.iter().enumerate() {
+        if positions.insert(*tag, position).is_some() {
+            return Err(if is_consumed(position) {
+                VerificationError::DuplicateNullifier(*tag)
+            } else {
+                VerificationError::DuplicateTag(*tag)
+            });
+        }
+    }
+    Ok(positions)
+}
+
+/// The public inputs of a logic proof that a forwarder call can be bound by.
+pub(crate) struct BindingInstance<'a> {
+    pub tag: Digest,
+    pub is_consumed: bool,
+    pub app_data: &'a [ExpirableBlob],
+}
+
+impl<'a> From<&'a LogicInstance> for BindingInstance<'a> {
+    fn from(instance: &'a LogicInstance) -> Self {
+        BindingInstance {
+            tag: instance.tag,
+            is_consumed: instance.is_consumed,
+            app_data: &instance.app_data,
+        }
+    }
+}
+
+/// Checks that each call is bound by a logic proof whose tag is in `tags`,
+/// with the same consumed flag, and which is the tag of the carrier resource
+/// when created. Every proof outputting the binding is a candidate, and
+/// `proves_logic(call, proof)` tells whether the logic of proof `proof` is the
+/// logic of the carrier of call `call`.
+pub(crate) fn verify_forwarder_bindings<'a>(
+    calls: impl IntoIterator<Item = (&'a Resource, &'a ForwarderCalldata)>,
+    tags: &HashSet<(Digest, bool)>,
+    instances: &[BindingInstance],
+    proves_logic: impl Fn(usize, usize) -> bool,
+) -> Result<(), VerificationError> {
+    'calls: for (index, (resource, calldata)) in calls.into_iter().enumerate() {
+        let commitment = resource.commitment();
+        let mut bound = false;
+        for (proof_index, instance) in instances.iter().enumerate() {
+            if !instance
+                .app_data
+                .contains(&calldata.binding(&instance.tag, resource))
+            {
+                continue;
+            }
+            bound = true;
+            if tags.contains(&(instance.tag, instance.is_consumed))
+                && (instance.is_consumed || instance.tag == commitment)
+                && proves_logic(index, proof_index)
+            {
+                continue 'calls;
+            }
+        }
+        return Err(if bound {
+            VerificationError::ForwarderResourceMismatch(index)
+        } else {
+            VerificationError::ForwarderCallNotBound(index)
+        });
+    }
+
+    Ok(())
+}
+
 impl Action {
     pub fn new(
         compliance_units: Vec<Receipt>,
@@ -39,6 +140,7 @@ impl Action {
     ) -> Self {
         Action {
             compliance_units,
+            multi_compliance_units: Vec::new(),
             logic_proofs,
             resource_forwarder_calldata_pairs,
         }
@@ -48,83 +150,210 @@ impl Action {
         &self.compliance_units
     }
 
+    pub fn with_multi_compliance_units(mut self, multi_compliance_units: Vec<Receipt>) -> Self {
+        self.multi_compliance_units = multi_compliance_units;
+        self
+    }
+
+    pub fn get_multi_compliance_units(&self) -> &Vec<Receipt> {
+        &self.multi_compliance_units
+    }
+
     pub fn get_logic_proofs(&self) -> &Vec<LogicProof> {
         &self.logic_proofs
     }
 
+    /// Returns the journals of the proofs, as checked by the aggregation
+    /// guest, see `Transaction::aggregate`.
+    pub fn get_journals(&self) -> ActionJournals {
+        ActionJournals {
+            compliance_journals: self
+                .compliance_units
+                .iter()
+                .map(|receipt| receipt.journal.bytes.clone())
+                .collect(),
+            multi_compliance_journals: self
+                .multi_compliance_units
+                .iter()
+                .map(|receipt| receipt.journal.bytes.clone())
+                .collect(),
+            logic_journals: self
+                .logic_proofs
+                .iter()
+                .map(|proof| (proof.verifying_key, proof.receipt.journal.bytes.clone()))
+                .collect(),
+        }
+    }
+
     pub fn get_resource_forwarder_calldata_pairs(&self) -> &Vec<(Resource, ForwarderCalldata)> {
         &self.resource_forwarder_calldata_pairs
     }
 
-    pub fn verify(&self) -> bool {
-        for receipt in &self.compliance_units {
+    pub fn verify(&self) -> Result<(), VerificationError> {
+        for (index, receipt) in self.compliance_units.iter().enumerate() {
             if !verify_proof(receipt, COMPLIANCE_GUEST_ID) {
-                return false;
+                return Err(VerificationError::InvalidComplianceReceipt(index));
             }
         }
 
-        let compliance_intances = self
-            .compliance_units
-            .iter()
-            .map(|receipt| receipt.journal.decode().unwrap())
-            .collect::<Vec<ComplianceInstance>>();
+        for (index, receipt) in self.multi_compliance_units.iter().enumerate() {
+            if !verify_proof(receipt, MULTI_COMPLIANCE_GUEST_ID) {
+                return Err(VerificationError::InvalidMultiComplianceReceipt(index));
+            }
+        }
+
+        self.verify_logic_instances()?;
+
+        for (index, proof) in self.logic_proofs.iter().enumerate() {
+            if !verify_proof(&proof.receipt, proof.verifying_key) {
+                return Err(VerificationError::InvalidLogicReceipt(index));
+            }
+        }
+
+        self.verify_forwarder_calls()
+    }
+
+    /// Checks the logic instances against the action tree built from the
+    /// compliance instances, without verifying the receipts: every tag must
+    /// have exactly one logic proof, of the right logic and consumed flag,
+    /// and nullifiers must be unique.
+    pub fn verify_logic_instances(&self) -> Result<(), VerificationError> {
+        let compliance_intances = self.get_multi_compliance_instances()?;
 
         // Construct the action tree
         let tags = compliance_intances
             .iter()
-            .flat_map(|instance| vec![instance.consumed_nullifier, instance.created_commitment])
+            .flat_map(|instance| instance.tags())
             .collect::<Vec<_>>();
         let logics = compliance_intances
             .iter()
-            .flat_map(|instance| vec![instance.consumed_logic_ref, instance.created_logic_ref])
+            .flat_map(|instance| instance.logic_refs())
+            .collect::<Vec<_>>();
+        let is_consumed = compliance_intances
+            .iter()
+            .flat_map(|instance| instance.is_consumed())
             .collect::<Vec<_>>();
-        let action_tree = MerkleTree::new(tags.clone());
+        let action_tree = MerkleTree::new(tags.clone())?;
         let root = action_tree.root();
 
-        for proof in &self.logic_proofs {
-            let instance: LogicInstance = proof.receipt.journal.decode().unwrap();
+        let positions = tag_positions(&tags, |position| is_consumed[position])?;
+
+        // Every tag must have exactly one logic proof
+        let mut proven = vec![false; tags.len()];
+        for (index, proof) in self.logic_proofs.iter().enumerate() {
+            let instance: LogicInstance = decode_journal(&proof.receipt)?;
 
             if root != instance.root {
-                return false;
+                return Err(VerificationError::RootMismatch(index));
+            }
+            if instance.action_tree_depth as usize != action_tree.depth() {
+                return Err(VerificationError::ActionTreeDepthMismatch(index));
             }
 
-            if let Some(index) = tags.iter().position(|&tag| tag == instance.tag) {
-                if proof.verifying_key != logics[index] {
-                    return false;
+            if let Some(&position) = positions.get(&instance.tag) {
+                if proof.verifying_key != logics[position] {
+                    return Err(VerificationError::VerifyingKeyMismatch(index));
+                }
+                if instance.is_consumed != is_consumed[position] {
+                    return Err(VerificationError::ConsumedFlagMismatch(index));
+                }
+                if std::mem::replace(&mut proven[position], true) {
+                    return Err(VerificationError::DuplicateLogicProof(index));
                 }
             } else {
-                return false;
+                return Err(VerificationError::TagNotFound(index));
             }
+        }
+        if let Some(position) = proven.iter().position(|&proven| !proven) {
+            return Err(VerificationError::MissingLogicProof(tags[position]));
+        }
 
-            if !verify_proof(&proof.receipt, proof.verifying_key) {
-                return false;
-            }
+        Ok(())
+    }
+
+    /// Checks that each forwarder call is bound in the app data of a logic
+    /// proof of its resource logic, over the tag of that proof and the
+    /// commitment of the resource. The tag must be a tag of this action and,
+    /// for a created resource, the commitment itself. The nullifier of a
+    /// consumed resource can't be derived without its key, so its logic is
+    /// trusted to only bind calls carried by the resource it consumes.
+    pub fn verify_forwarder_calls(&self) -> Result<(), VerificationError> {
+        if self.resource_forwarder_calldata_pairs.is_empty() {
+            return Ok(());
         }
 
-        true
+        let tags = self
+            .get_multi_compliance_instances()?
+            .iter()
+            .flat_map(|instance| instance.tags().into_iter().zip(instance.is_consumed()))
+            .collect::<HashSet<_>>();
+        let instances = self
+            .logic_proofs
+            .iter()
+            .map(|proof| decode_journal::<LogicInstance>(&proof.receipt))
+            .collect::<Result<Vec<_>, _>>()?;
+        verify_forwarder_bindings(
+            self.resource_forwarder_calldata_pairs
+                .iter()
+                .map(|(resource, calldata)| (resource, calldata)),
+            &tags,
+            &instances
+                .iter()
+                .map(BindingInstance::from)
+                .collect::<Vec<_>>(),
+            |call, proof| {
+                self.logic_proofs[proof].verifying_key
+                    == self.resource_forwarder_calldata_pairs[call].0.logic_ref
+            },
+        )
+    }
+
+    pub fn get_compliance_instances(&self) -> Result<Vec<ComplianceInstance>, VerificationError> {
+        self.compliance_units.iter().map(decode_journal).collect()
+    }
+
+    /// Returns the instances of all the compliance units, the single units
+    /// being normalized to multi units with one consumed and one created
+    /// resource.
+    pub fn get_multi_compliance_instances(
+        &self,
+    ) -> Result<Vec<MultiComplianceInstance>, VerificationError> {
+        let mut instances = self
+            .get_compliance_instances()?
+            .into_iter()
+            .map(MultiComplianceInstance::from)
+            .collect::<Vec<_>>();
+        for receipt in &self.multi_compliance_units {
+            instances.push(decode_journal(receipt)?);
+        }
+        Ok(instances)
     }
 
-    pub fn get_delta(&self) -> Vec<ProjectivePoint> {
-        self.compliance_units
+    pub fn get_delta(&self) -> Result<Vec<ProjectivePoint>, VerificationError> {
+        Ok(self
+            .get_multi_compliance_instances()?
             .iter()
-            .map(|receipt| {
-                let instance: ComplianceInstance = receipt.journal.decode().unwrap();
-                instance.delta_projective()
-            })
-            .collect()
+            .map(|instance| instance.delta_projective())
+            .collect())
     }
 
-    pub fn get_delta_msg(&self) -> Vec<u8> {
+    pub fn get_delta_msg(&self) -> Result<Vec<u8>, VerificationError> {
         let mut msg = Vec::new();
-        for receipt in &self.compliance_units {
-            let instance: ComplianceInstance = receipt.journal.decode().unwrap();
+        for instance in self.get_multi_compliance_instances()? {
             msg.extend_from_slice(&instance.delta_msg());
         }
-        msg
+        Ok(msg)
     }
 }
 
-pub fn create_an_action(nonce: u8) -> (Action, DeltaWitness) {
+fn decode_journal<T: DeserializeOwned>(receipt: &Receipt) -> Result<T, VerificationError> {
+    receipt
+        .journal
+        .decode()
+        .map_err(|e| VerificationError::JournalDecode(e.to_string()))
+}
+
+pub fn create_an_action(nonce: u8) -> Result<(Action, DeltaWitness), ProvingError> {
     let nf_key = NullifierKey::new(Digest::default());
     let nf_key_cm = nf_key.commit();
     let mut consumed_resource = Resource {
@@ -141,17 +370,25 @@ pub fn create_an_action(nonce: u8) -> (Action, DeltaWitness) {
         nf_key,
         created_resource,
     );
-    let compliance_receipt = groth16_prove(&compliance_witness, COMPLIANCE_GUEST_ELF);
+    let compliance_receipt = groth16_prove(&compliance_witness, COMPLIANCE_GUEST_ELF)?;
 
-    let consumed_resource_nf = consumed_resource.nullifier(&nf_key).unwrap();
+    let consumed_resource_nf = consumed_resource
+        .nullifier(&nf_key)
+        .ok_or(ProvingError::InvalidNullifierKey)?;
     let created_resource_cm = created_resource.commitment();
-    let action_tree = MerkleTree::new(vec![consumed_resource_nf, created_resource_cm]);
-    let consumed_resource_path = action_tree.generate_path(consumed_resource_nf).unwrap();
-    let created_resource_path = action_tree.generate_path(created_resource_cm).unwrap();
+    let action_tree = MerkleTree::new(vec![consumed_resource_nf, created_resource_cm])?;
+    let depth_error = ProvingError::ActionTreeDepth {
+        expected: ACTION_TREE_DEPTH,
+        found: action_tree.depth(),
+    };
+    let [consumed_resource_path, created_resource_path] = action_tree
+        .generate_paths::<ACTION_TREE_DEPTH>()
+        .and_then(|paths| paths.try_into().ok())
+        .ok_or(depth_error)?;
 
     let consumed_logic_witness =
         TrivialLogicWitness::new(consumed_resource, consumed_resource_path, nf_key, true);
-    let consumed_logic_receipt = groth16_prove(&consumed_logic_witness, TEST_GUEST_ELF);
+    let consumed_logic_receipt = groth16_prove(&consumed_logic_witness, TEST_GUEST_ELF)?;
     let consumed_logic_proof = LogicProof {
         receipt: consumed_logic_receipt,
         verifying_key: TEST_GUEST_ID.into(),
@@ -159,7 +396,7 @@ pub fn create_an_action(nonce: u8) -> (Action, DeltaWitness) {
 
     let created_logic_witness =
         TrivialLogicWitness::new(created_resource, created_resource_path, nf_key, false);
-    let created_logic_receipt = groth16_prove(&created_logic_witness, TEST_GUEST_ELF);
+    let created_logic_receipt = groth16_prove(&created_logic_witness, TEST_GUEST_ELF)?;
     let created_logic_proof = LogicProof {
         receipt: created_logic_receipt,
         verifying_key: TEST_GUEST_ID.into(),
@@ -174,21 +411,21 @@ pub fn create_an_action(nonce: u8) -> (Action, DeltaWitness) {
         logic_proofs,
         resource_forwarder_calldata_pairs,
     );
-    assert!(action.verify());
+    action.verify()?;
 
     let delta_witness = DeltaWitness::from_scalars(&[compliance_witness.rcv]);
-    (action, delta_witness)
+    Ok((action, delta_witness))
 }
 
-pub fn create_multiple_actions(n: usize) -> (Vec<Action>, DeltaWitness) {
+pub fn create_multiple_actions(n: usize) -> Result<(Vec<Action>, DeltaWitness), ProvingError> {
     let mut actions = Vec::new();
     let mut delta_witnesses = Vec::new();
     for i in 0..n {
-        let (action, delta_witness) = create_an_action(i as u8);
+        let (action, delta_witness) = create_an_action(i as u8)?;
         actions.push(action);
         delta_witnesses.push(delta_witness);
     }
-    (actions, DeltaWitness::compress(&delta_witnesses))
+    Ok((actions, DeltaWitness::compress(&delta_witnesses)))
 }
 
 #[cfg(test)]
@@ -197,6 +434,269 @@ pub mod tests {
 
     #[test]
     fn test_action() {
-        let _ = create_an_action(1);
+        let _ = create_an_action(1).unwrap();
+    }
+
+    // A multi compliance unit consuming two ephemeral resources and creating
+    // one, proven in dev mode
+    pub fn create_a_multi_compliance_action(nonce: u8) -> (Action, DeltaWitness) {
+        use crate::constants::MULTI_COMPLIANCE_GUEST_ELF;
+        use crate::utils::{prove, ProofMode};
+        use aarm_core::compliance::{ConsumedResourceWitness, MultiComplianceWitness};
+        use aarm_core::merkle_path::MerklePath;
+
+        let nf_key = NullifierKey::new(Digest::default());
+        let resource = |index: u8| {
+            let mut resource = Resource {
+                logic_ref: Digest::new(TEST_GUEST_ID),
+                nk_commitment: nf_key.commit(),
+                is_ephemeral: true,
+                ..Default::default()
+            };
+            resource.nonce[0] = nonce;
+            resource.nonce[1] = index;
+            resource
+        };
+        let consumed = [resource(1), resource(2)];
+        let created = [resource(3)];
+
+        let compliance_witness = MultiComplianceWitness::<COMMITMENT_TREE_DEPTH>::from_resources(
+            consumed
+                .iter()
+                .map(|r| ConsumedResourceWitness::new(*r, nf_key, MerklePath::default()))
+                .collect(),
+            created.to_vec(),
+        );
+        let compliance_receipt = prove(
+            &compliance_witness,
+            MULTI_COMPLIANCE_GUEST_ELF,
+            ProofMode::Dev,
+        )
+        .unwrap();
+
+        let instance = compliance_witness.constrain();
+        let action_tree = MerkleTree::new(instance.tags()).unwrap();
+        let logic_proofs = consumed
+            .iter()
+            .map(|r| (*r, true))
+            .chain(created.iter().map(|r| (*r, false)))
+            .zip(instance.tags())
+            .map(|((resource, is_consumed), tag)| {
+                let path = action_tree.generate_path::<ACTION_TREE_DEPTH>(tag).unwrap();
+                let witness = TrivialLogicWitness::new(resource, path, nf_key, is_consumed);
+                LogicProof {
+                    receipt: prove(&witness, TEST_GUEST_ELF, ProofMode::Dev).unwrap(),
+                    verifying_key: TEST_GUEST_ID.into(),
+                }
+            })
+            .collect();
+
+        let action = Action::new(vec![], logic_proofs, vec![])
+            .with_multi_compliance_units(vec![compliance_receipt]);
+        (
+            action,
+            DeltaWitness::from_scalars(&[compliance_witness.rcv]),
+        )
+    }
+
+    #[test]
+    fn test_multi_compliance_unit() {
+        let (action, _) = create_a_multi_compliance_action(1);
+        assert!(action.verify().is_ok());
+    }
+
+    #[test]
+    fn test_logic_instance_completeness() {
+        use crate::utils::fake_receipt;
+
+        let logic_ref = Digest::from([1u32; 8]);
+        let compliance = ComplianceInstance {
+            consumed_nullifier: Digest::from([2u32; 8]),
+            consumed_logic_ref: logic_ref,
+            created_commitment: Digest::from([3u32; 8]),
+            created_logic_ref: logic_ref,
+            ..Default::default()
+        };
+        let root = MerkleTree::new(vec![
+            compliance.consumed_nullifier,
+            compliance.created_commitment,
+        ])
+        .unwrap()
+        .root();
+        let logic_proof_at_depth = |tag: Digest, is_consumed: bool, depth: usize| LogicProof {
+            receipt: fake_receipt(
+                logic_ref,
+                &LogicInstance {
+                    tag,
+                    is_consumed,
+                    root,
+                    action_tree_depth: depth as u32,
+                    ..Default::default()
+                },
+            ),
+            verifying_key: logic_ref,
+        };
+        let logic_proof = |tag: Digest, is_consumed: bool| {
+            logic_proof_at_depth(tag, is_consumed, ACTION_TREE_DEPTH)
+        };
+        let action = |logic_proofs: Vec<LogicProof>| {
+            Action::new(
+                vec![fake_receipt(COMPLIANCE_GUEST_ID, &compliance)],
+                logic_proofs,
+                vec![],
+            )
+        };
+        let consumed = logic_proof(compliance.consumed_nullifier, true);
+        let created = logic_proof(compliance.created_commitment, false);
+
+        assert!(action(vec![consumed.clone(), created.clone()])
+            .verify_logic_instances()
+            .is_ok());
+        assert_eq!(
+            action(vec![consumed.clone()]).verify_logic_instances(),
+            Err(VerificationError::MissingLogicProof(
+                compliance.created_commitment
+            ))
+        );
+        assert_eq!(
+            action(vec![consumed.clone(), created.clone(), consumed.clone()])
+                .verify_logic_instances(),
+            Err(VerificationError::DuplicateLogicProof(2))
+        );
+        assert_eq!(
+            action(vec![
+                consumed.clone(),
+                logic_proof(compliance.created_commitment, true)
+            ])
+            .verify_logic_instances(),
+            Err(VerificationError::ConsumedFlagMismatch(1))
+        );
+        assert_eq!(
+            action(vec![
+                consumed.clone(),
+                logic_proof_at_depth(compliance.created_commitment, false, ACTION_TREE_DEPTH + 1)
+            ])
+            .verify_logic_instances(),
+            Err(VerificationError::ActionTreeDepthMismatch(1))
+        );
+
+        // Two units consuming the same resource
+        let mut twice = action(vec![]);
+        twice
+            .compliance_units
+            .push(twice.compliance_units[0].clone());
+        assert_eq!(
+            twice.verify_logic_instances(),
+            Err(VerificationError::DuplicateNullifier(
+                compliance.consumed_nullifier
+            ))
+        );
+
+        // Two units creating the same resource
+        let other = ComplianceInstance {
+            consumed_nullifier: Digest::from([4u32; 8]),
+            ..compliance.clone()
+        };
+        let mut twice = action(vec![]);
+        twice
+            .compliance_units
+            .push(fake_receipt(COMPLIANCE_GUEST_ID, &other));
+        assert_eq!(
+            twice.verify_logic_instances(),
+            Err(VerificationError::DuplicateTag(
+                compliance.created_commitment
+            ))
+        );
+    }
+
+    #[test]
+    fn test_forwarder_call_binding() {
+        use crate::utils::fake_receipt;
+
+        let resource = Resource {
+            logic_ref: Digest::from([1u32; 8]),
+            ..Default::default()
+        };
+        let nullifier = Digest::from([5u32; 8]);
+        let calldata = ForwarderCalldata {
+            untrusted_forwarder: [2u8; 20],
+            input: vec![3],
+            output: vec![4],
+        };
+        let compliance_receipt = fake_receipt(
+            COMPLIANCE_GUEST_ID,
+            &ComplianceInstance {
+                consumed_nullifier: nullifier,
+                created_commitment: resource.commitment(),
+                ..Default::default()
+            },
+        );
+        let logic_proof = |verifying_key: Digest, tag: Digest, is_consumed: bool| LogicProof {
+            receipt: fake_receipt(
+                verifying_key,
+                &LogicInstance {
+                    tag,
+                    is_consumed,
+                    app_data: vec![calldata.binding(&tag, &resource)],
+                    ..Default::default()
+                },
+            ),
+            verifying_key,
+        };
+        let action = |proofs: Vec<LogicProof>, calldata: &ForwarderCalldata| {
+            Action::new(
+                vec![compliance_receipt.clone()],
+                proofs,
+                vec![(resource, calldata.clone())],
+            )
+        };
+
+        let created = logic_proof(resource.logic_ref, resource.commitment(), false);
+        assert!(action(vec![created.clone()], &calldata)
+            .verify_forwarder_calls()
+            .is_ok());
+
+        // Bound by the proof of the consumed carrier
+        let consumed = logic_proof(resource.logic_ref, nullifier, true);
+        assert!(action(vec![consumed], &calldata)
+            .verify_forwarder_calls()
+            .is_ok());
+
+        // The relayer replaces the call
+        let mut other = calldata.clone();
+        other.input = vec![5];
+        assert_eq!(
+            action(vec![created.clone()], &other).verify_forwarder_calls(),
+            Err(VerificationError::ForwarderCallNotBound(0))
+        );
+
+        // The call is bound by the proof of another created resource
+        assert_eq!(
+            action(
+                vec![logic_proof(resource.logic_ref, Digest::ZERO, false)],
+                &calldata
+            )
+            .verify_forwarder_calls(),
+            Err(VerificationError::ForwarderResourceMismatch(0))
+        );
+
+        // The nullifier of the consumed carrier is not in the action
+        assert_eq!(
+            action(
+                vec![logic_proof(resource.logic_ref, Digest::ZERO, true)],
+                &calldata
+            )
+            .verify_forwarder_calls(),
+            Err(VerificationError::ForwarderResourceMismatch(0))
+        );
+
+        // Every proof outputting the binding is checked, not just the first
+        let other_logic = logic_proof(Digest::from([6u32; 8]), nullifier, true);
+        assert!(action(vec![other_logic.clone()], &calldata)
+            .verify_forwarder_calls()
+            .is_err());
+        assert!(action(vec![other_logic, created], &calldata)
+            .verify_forwarder_calls()
+            .is_ok());
     }
 }
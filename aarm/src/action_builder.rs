@@ -0,0 +1,245 @@
+use crate::{
+    action::{Action, ForwarderCalldata},
+    constants::COMPLIANCE_GUEST_ELF,
+    error::ProvingError,
+    logic_proof::{LogicProof, LogicProver, PaddingResourceLogic},
+    utils::{prove, ProofMode},
+};
+use aarm_core::{
+    action_tree::{MerkleTree, ACTION_TREE_DEPTH},
+    compliance::ComplianceWitness,
+    constants::COMMITMENT_TREE_DEPTH,
+    delta_proof::DeltaWitness,
+    merkle_path::MerklePath,
+    nullifier_key::NullifierKey,
+    resource::Resource,
+};
+
+/// Builds the logic proof of a resource once the action tree is known. It is
+/// given the index of the resource tag in the action tree and its existence
+/// path, which is the right one even if the tag appears twice.
+pub type LogicBuilder<'a> = Box<
+    dyn FnOnce(usize, MerklePath<ACTION_TREE_DEPTH>, ProofMode) -> Result<LogicProof, ProvingError>
+        + 'a,
+>;
+
+struct ConsumedEntry<'a> {
+    resource: Resource,
+    nf_key: NullifierKey,
+    merkle_path: MerklePath<COMMITMENT_TREE_DEPTH>,
+    logic: LogicBuilder<'a>,
+}
+
+struct CreatedEntry<'a> {
+    resource: Resource,
+    logic: LogicBuilder<'a>,
+}
+
+/// Pairs consumed and created resources into compliance units, padding the
+/// side with fewer resources, and proves the action.
+///
+/// The action tree lists the tags of each compliance unit in order, i.e.
+/// `[nf_1, cm_1, nf_2, cm_2, ...]`. The logic circuits take paths of
+/// `ACTION_TREE_DEPTH`, so an action holds at most `1 << ACTION_TREE_DEPTH`
+/// tags, i.e. 8 compliance units, and `build` fails with
+/// `ProvingError::ActionTreeDepth` beyond that.
+#[derive(Default)]
+pub struct ActionBuilder<'a> {
+    consumed: Vec<ConsumedEntry<'a>>,
+    created: Vec<CreatedEntry<'a>>,
+    forwarder_calls: Vec<(Resource, ForwarderCalldata)>,
+}
+
+impl<'a> ActionBuilder<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a consumed resource. `merkle_path` is the path in the commitment
+    /// tree and is ignored for ephemeral resources.
+    pub fn add_consumed(
+        &mut self,
+        resource: Resource,
+        nf_key: NullifierKey,
+        merkle_path: MerklePath<COMMITMENT_TREE_DEPTH>,
+        logic: LogicBuilder<'a>,
+    ) -> &mut Self {
+        self.consumed.push(ConsumedEntry {
+            resource,
+            nf_key,
+            merkle_path,
+            logic,
+        });
+        self
+    }
+
+    pub fn add_created(&mut self, resource: Resource, logic: LogicBuilder<'a>) -> &mut Self {
+        self.created.push(CreatedEntry { resource, logic });
+        self
+    }
+
+    /// Attaches a forwarder call carried by `resource`. The logic proof of
+    /// the resource must output `calldata.binding(&tag, &resource)` in its
+    /// app data, `tag` being the tag it proves.
+    pub fn add_forwarder_call(
+        &mut self,
+        resource: Resource,
+        calldata: ForwarderCalldata,
+    ) -> &mut Self {
+        self.forwarder_calls.push((resource, calldata));
+        self
+    }
+
+    pub fn build(mut self, mode: ProofMode) -> Result<(Action, DeltaWitness), ProvingError> {
+        self.pad();
+
+        let mut tags = Vec::new();
+        for (consumed, created) in self.consumed.iter().zip(&self.created) {
+            let nf = consumed
+                .resource
+                .nullifier(&consumed.nf_key)
+                .ok_or(ProvingError::InvalidNullifierKey)?;
+            tags.push(nf);
+            tags.push(created.resource.commitment());
+        }
+        let action_tree = MerkleTree::new(tags)?;
+        let paths = action_tree.generate_paths::<ACTION_TREE_DEPTH>().ok_or(
+            ProvingError::ActionTreeDepth {
+                expected: ACTION_TREE_DEPTH,
+                found: action_tree.depth(),
+            },
+        )?;
+
+        let mut compliance_units = Vec::new();
+        let mut logic_proofs = Vec::new();
+        let mut rcvs = Vec::new();
+        for (index, (consumed, created)) in self.consumed.into_iter().zip(self.created).enumerate()
+        {
+            let compliance_witness = if consumed.resource.is_ephemeral {
+                ComplianceWitness::<COMMITMENT_TREE_DEPTH>::from_resources(
+                    consumed.resource,
+                    consumed.nf_key,
+                    created.resource,
+                )
+            } else {
+                ComplianceWitness::<COMMITMENT_TREE_DEPTH>::from_resources_with_path(
+                    consumed.resource,
+                    consumed.nf_key,
+                    consumed.merkle_path,
+                    created.resource,
+                )
+            };
+            compliance_units.push(prove(&compliance_witness, COMPLIANCE_GUEST_ELF, mode)?);
+            rcvs.push(compliance_witness.rcv);
+
+            let (nf_index, cm_index) = (2 * index, 2 * index + 1);
+            logic_proofs.push((consumed.logic)(nf_index, paths[nf_index], mode)?);
+            logic_proofs.push((created.logic)(cm_index, paths[cm_index], mode)?);
+        }
+
+        let action = Action::new(compliance_units, logic_proofs, self.forwarder_calls);
+        Ok((action, DeltaWitness::from_scalars(&rcvs)))
+    }
+
+    // Adds padding resources until there are as many consumed as created
+    // resources
+    fn pad(&mut self) {
+        while self.consumed.len() < self.created.len() {
+            let (nf_key, nk_commitment) = NullifierKey::random_pair();
+            let resource = PaddingResourceLogic::create_padding_resource(nk_commitment);
+            self.add_consumed(
+                resource,
+                nf_key,
+                MerklePath::default(),
+                Box::new(move |_, path, mode| {
+                    PaddingResourceLogic::new(resource, path, nf_key, true).prove(mode)
+                }),
+            );
+        }
+        while self.created.len() < self.consumed.len() {
+            let (nf_key, nk_commitment) = NullifierKey::random_pair();
+            let resource = PaddingResourceLogic::create_padding_resource(nk_commitment);
+            self.add_created(
+                resource,
+                Box::new(move |_, path, mode| {
+                    PaddingResourceLogic::new(resource, path, nf_key, false).prove(mode)
+                }),
+            );
+        }
+    }
+}
+
+#[test]
+fn test_action_builder_padding() {
+    use crate::constants::{TEST_GUEST_ELF, TEST_GUEST_ID};
+    use aarm_core::resource_logic::TrivialLogicWitness;
+    use risc0_zkvm::Digest;
+
+    let (nf_key, nk_commitment) = NullifierKey::random_pair();
+    let test_logic = |resource: Resource, is_consumed: bool| -> LogicBuilder {
+        Box::new(move |_, path, mode| {
+            let witness = TrivialLogicWitness::new(resource, path, nf_key, is_consumed);
+            Ok(LogicProof {
+                receipt: prove(&witness, TEST_GUEST_ELF, mode)?,
+                verifying_key: TEST_GUEST_ID.into(),
+            })
+        })
+    };
+
+    // Two consumed resources and a single created resource
+    let mut builder = ActionBuilder::new();
+    for nonce in 0..3u8 {
+        let mut resource = Resource {
+            logic_ref: Digest::new(TEST_GUEST_ID),
+            nk_commitment,
+            is_ephemeral: true,
+            ..Default::default()
+        };
+        resource.nonce[0] = nonce;
+        if nonce < 2 {
+            builder.add_consumed(
+                resource,
+                nf_key,
+                MerklePath::default(),
+                test_logic(resource, true),
+            );
+        } else {
+            builder.add_created(resource, test_logic(resource, false));
+        }
+    }
+
+    let (action, _) = builder.build(ProofMode::Dev).unwrap();
+    assert_eq!(action.compliance_units.len(), 2);
+    assert_eq!(action.logic_proofs.len(), 4);
+    assert!(action.verify().is_ok());
+
+    // A consumed resource must come with its nullifier key
+    let (other_nf_key, _) = NullifierKey::random_pair();
+    let resource = PaddingResourceLogic::create_padding_resource(nk_commitment);
+    let mut builder = ActionBuilder::new();
+    builder.add_consumed(
+        resource,
+        other_nf_key,
+        MerklePath::default(),
+        Box::new(|_, _, _| unreachable!()),
+    );
+    assert!(matches!(
+        builder.build(ProofMode::Dev),
+        Err(ProvingError::InvalidNullifierKey)
+    ));
+
+    // Nine compliance units need a deeper tree than the logic circuits take
+    let mut builder = ActionBuilder::new();
+    for nonce in 0..9u8 {
+        let mut resource = PaddingResourceLogic::create_padding_resource(nk_commitment);
+        resource.nonce[0] = nonce;
+        builder.add_created(resource, Box::new(|_, _, _| unreachable!()));
+    }
+    assert!(matches!(
+        builder.build(ProofMode::Dev),
+        Err(ProvingError::ActionTreeDepth {
+            expected: ACTION_TREE_DEPTH,
+            found: 5,
+        })
+    ));
+}
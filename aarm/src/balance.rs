@@ -0,0 +1,279 @@
+use crate::{
+    error::{BalanceError, VerificationError},
+    transaction::Transaction,
+};
+use aarm_core::{delta_proof::DeltaWitness, resource::Resource};
+use k256::{ProjectivePoint, Scalar};
+use risc0_zkvm::Digest;
+use std::collections::BTreeMap;
+
+/// The fungibility domain of a resource. Resources of the same kind balance
+/// each other, see `Resource::kind`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ResourceKind {
+    pub logic_ref: Digest,
+    pub label_ref: Digest,
+}
+
+impl ResourceKind {
+    /// The curve point the quantities of this kind are committed to.
+    pub fn point(&self) -> ProjectivePoint {
+        Resource {
+            logic_ref: self.logic_ref,
+            label_ref: self.label_ref,
+            ..Default::default()
+        }
+        .kind()
+    }
+}
+
+impl From<&Resource> for ResourceKind {
+    fn from(resource: &Resource) -> Self {
+        ResourceKind {
+            logic_ref: resource.logic_ref,
+            label_ref: resource.label_ref,
+        }
+    }
+}
+
+/// The consumed and created quantities of a kind.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KindBalance {
+    pub consumed: u128,
+    pub created: u128,
+}
+
+impl KindBalance {
+    pub fn is_balanced(&self) -> bool {
+        self.consumed == self.created
+    }
+
+    /// The quantity consumed but not created, which another transaction
+    /// must create.
+    pub fn surplus(&self) -> u128 {
+        self.consumed.saturating_sub(self.created)
+    }
+
+    /// The quantity created but not consumed, which another transaction
+    /// must consume.
+    pub fn deficit(&self) -> u128 {
+        self.created.saturating_sub(self.consumed)
+    }
+
+    fn net_scalar(&self) -> Scalar {
+        Scalar::from(self.consumed) - Scalar::from(self.created)
+    }
+}
+
+/// The net quantities per kind of the resources of a transaction, as known
+/// to its builder. A transaction only verifies if all the kinds balance, and
+/// partial transactions can be composed when their reports add up to a
+/// balanced one.
+///
+/// Quantities are summed as `u128`, and an update that would overflow is
+/// rejected with the report left unchanged.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BalanceReport {
+    balances: BTreeMap<ResourceKind, KindBalance>,
+}
+
+impl BalanceReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_resources(
+        consumed: &[Resource],
+        created: &[Resource],
+    ) -> Result<Self, BalanceError> {
+        let mut report = Self::new();
+        for resource in consumed {
+            report.add_consumed(resource)?;
+        }
+        for resource in created {
+            report.add_created(resource)?;
+        }
+        Ok(report)
+    }
+
+    pub fn add_consumed(&mut self, resource: &Resource) -> Result<&mut Self, BalanceError> {
+        let kind = ResourceKind::from(resource);
+        let mut balance = self.balance(&kind);
+        balance.consumed = add_quantity(&kind, balance.consumed, resource.quantity)?;
+        self.balances.insert(kind, balance);
+        Ok(self)
+    }
+
+    pub fn add_created(&mut self, resource: &Resource) -> Result<&mut Self, BalanceError> {
+        let kind = ResourceKind::from(resource);
+        let mut balance = self.balance(&kind);
+        balance.created = add_quantity(&kind, balance.created, resource.quantity)?;
+        self.balances.insert(kind, balance);
+        Ok(self)
+    }
+
+    /// Adds the quantities of another report, e.g. of a partial transaction
+    /// to compose with.
+    pub fn merge(&mut self, other: &BalanceReport) -> Result<&mut Self, BalanceError> {
+        let mut merged = self.balances.clone();
+        for (kind, other) in &other.balances {
+            let balance = merged.entry(*kind).or_default();
+            balance.consumed = add_quantity(kind, balance.consumed, other.consumed)?;
+            balance.created = add_quantity(kind, balance.created, other.created)?;
+        }
+        self.balances = merged;
+        Ok(self)
+    }
+
+    pub fn balance(&self, kind: &ResourceKind) -> KindBalance {
+        self.balances.get(kind).copied().unwrap_or_default()
+    }
+
+    pub fn kinds(&self) -> impl Iterator<Item = (&ResourceKind, &KindBalance)> {
+        self.balances.iter()
+    }
+
+    pub fn unbalanced(&self) -> impl Iterator<Item = (&ResourceKind, &KindBalance)> {
+        self.kinds().filter(|(_, balance)| !balance.is_balanced())
+    }
+
+    pub fn is_balanced(&self) -> bool {
+        self.unbalanced().next().is_none()
+    }
+
+    /// The sum of the deltas of the resources without their randomness.
+    pub fn delta(&self) -> ProjectivePoint {
+        self.balances
+            .iter()
+            .fold(ProjectivePoint::IDENTITY, |acc, (kind, balance)| {
+                acc + kind.point() * balance.net_scalar()
+            })
+    }
+
+    /// Checks that the compliance deltas of `tx` commit to exactly the
+    /// quantities of this report, given the delta witness of the
+    /// transaction. This holds for partial transactions too, which lets a
+    /// solver trust a report before composing.
+    pub fn matches_delta(
+        &self,
+        tx: &Transaction,
+        witness: &DeltaWitness,
+    ) -> Result<bool, VerificationError> {
+        let mut tx_delta = ProjectivePoint::IDENTITY;
+        for instance in tx.get_multi_compliance_instances()? {
+            tx_delta += instance.delta_projective();
+        }
+        Ok(tx_delta == self.delta() + witness.commitment())
+    }
+}
+
+fn add_quantity(kind: &ResourceKind, lhs: u128, rhs: u128) -> Result<u128, BalanceError> {
+    lhs.checked_add(rhs)
+        .ok_or(BalanceError::QuantityOverflow(*kind))
+}
+
+#[test]
+fn test_balance_report() {
+    let kind_a = Resource {
+        logic_ref: Digest::from([1u32; 8]),
+        quantity: 10,
+        ..Default::default()
+    };
+    let kind_b = Resource {
+        label_ref: Digest::from([2u32; 8]),
+        quantity: 5,
+        ..Default::default()
+    };
+
+    // Alice gives 10 A for 5 B
+    let alice = BalanceReport::from_resources(&[kind_a], &[kind_b]).unwrap();
+    assert!(!alice.is_balanced());
+    let a = alice.balance(&(&kind_a).into());
+    assert_eq!((a.surplus(), a.deficit()), (10, 0));
+    let b = alice.balance(&(&kind_b).into());
+    assert_eq!((b.surplus(), b.deficit()), (0, 5));
+
+    // Bob gives 5 B for 10 A
+    let bob = BalanceReport::from_resources(&[kind_b], &[kind_a]).unwrap();
+    let mut swap = alice.clone();
+    swap.merge(&bob).unwrap();
+    assert!(swap.is_balanced());
+    assert_eq!(swap.delta(), ProjectivePoint::IDENTITY);
+    assert_eq!(alice.delta() + bob.delta(), ProjectivePoint::IDENTITY);
+    assert_eq!(
+        alice.delta(),
+        kind_a.kind() * Scalar::from(10u64) - kind_b.kind() * Scalar::from(5u64)
+    );
+}
+
+#[test]
+fn test_balance_overflow() {
+    let resource = Resource {
+        quantity: u128::MAX,
+        ..Default::default()
+    };
+    let kind = ResourceKind::from(&resource);
+
+    let mut report = BalanceReport::from_resources(&[resource], &[]).unwrap();
+    assert_eq!(
+        report.add_consumed(&resource).unwrap_err(),
+        BalanceError::QuantityOverflow(kind)
+    );
+    assert_eq!(report.balance(&kind).consumed, u128::MAX);
+
+    // A failed merge leaves the report unchanged
+    let other = BalanceReport::from_resources(&[resource], &[resource]).unwrap();
+    let mut merged = BalanceReport::from_resources(&[], &[resource]).unwrap();
+    let before = merged.clone();
+    assert!(merged.merge(&other).is_err());
+    assert_eq!(merged, before);
+}
+
+#[test]
+fn test_matches_delta() {
+    use crate::{
+        action::Action, constants::COMPLIANCE_GUEST_ID, transaction::Delta, utils::fake_receipt,
+    };
+    use aarm_core::{
+        compliance::ComplianceWitness, constants::COMMITMENT_TREE_DEPTH,
+        nullifier_key::NullifierKey,
+    };
+
+    let nf_key = NullifierKey::new(Digest::default());
+    let consumed = Resource {
+        logic_ref: Digest::from([1u32; 8]),
+        quantity: 10,
+        nk_commitment: nf_key.commit(),
+        ..Default::default()
+    };
+    let created = Resource {
+        label_ref: Digest::from([2u32; 8]),
+        quantity: 5,
+        ..Default::default()
+    };
+    let compliance_witness =
+        ComplianceWitness::<COMMITMENT_TREE_DEPTH>::from_resources(consumed, nf_key, created);
+    let action = Action::new(
+        vec![fake_receipt(
+            COMPLIANCE_GUEST_ID,
+            &compliance_witness.constrain(),
+        )],
+        vec![],
+        vec![],
+    );
+    let witness = DeltaWitness::from_scalars(&[compliance_witness.rcv]);
+    let tx = Transaction::new(vec![action], Delta::Witness(witness.clone()));
+
+    let report = BalanceReport::from_resources(&[consumed], &[created]).unwrap();
+    assert!(report.matches_delta(&tx, &witness).unwrap());
+
+    // Another quantity of the created kind
+    let mut other = created;
+    other.quantity = 4;
+    let mismatch = BalanceReport::from_resources(&[consumed], &[other]).unwrap();
+    assert!(!mismatch.matches_delta(&tx, &witness).unwrap());
+
+    // Another delta witness
+    let other_witness = DeltaWitness::from_scalars(&[compliance_witness.rcv, Scalar::ONE]);
+    assert!(!report.matches_delta(&tx, &other_witness).unwrap());
+}
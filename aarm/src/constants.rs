@@ -3,23 +3,60 @@ use risc0_zkvm::Digest;
 pub const COMPLIANCE_GUEST_ELF: &[u8] = include_bytes!("../elfs/compliance_elf.bin");
 pub const PADDING_GUEST_ELF: &[u8] = include_bytes!("../elfs/padding_logic_elf.bin");
 pub const TEST_GUEST_ELF: &[u8] = include_bytes!("../elfs/test_logic_elf.bin");
+pub const MULTI_COMPLIANCE_GUEST_ELF: &[u8] = include_bytes!("../elfs/multi_compliance_elf.bin");
+pub const PRIVATE_COMPLIANCE_GUEST_ELF: &[u8] =
+    include_bytes!("../elfs/private_compliance_elf.bin");
+pub const LOGIC_WRAPPER_GUEST_ELF: &[u8] = include_bytes!("../elfs/logic_wrapper_elf.bin");
+pub const AGGREGATION_GUEST_ELF: &[u8] = include_bytes!("../elfs/aggregation_elf.bin");
 
 pub const COMPLIANCE_GUEST_ID: [u32; 8] = [
     2701349585, 2527110832, 1097996496, 2632817458, 249378437, 3823558497, 3512528456, 3033479435,
 ];
 
+pub const MULTI_COMPLIANCE_GUEST_ID: [u32; 8] = [
+    1577173290, 247862576, 2530399342, 1865641699, 3648903537, 909588142, 1482726198, 1422356994,
+];
+
+pub const PRIVATE_COMPLIANCE_GUEST_ID: [u32; 8] = [
+    1770906981, 271846323, 1749624693, 3641844184, 335811101, 1853063070, 1429766472, 200539172,
+];
+
+pub const LOGIC_WRAPPER_GUEST_ID: [u32; 8] = [
+    3202108608, 54876331, 2043721438, 3890947962, 569910920, 3738611853, 2202398447, 3189710355,
+];
+
+pub const AGGREGATION_GUEST_ID: [u32; 8] = [
+    959895319, 2806117981, 1465105792, 1172547386, 187998842, 1750238849, 1032596970, 3254862981,
+];
+
 pub const PADDING_GUEST_ID: [u32; 8] = [
-    3568301530, 3855691811, 2315865068, 3130072989, 1647038886, 2441233379, 1348193728, 1587710096,
+    1920048366, 4057775262, 1423049720, 2885255809, 2377099464, 976324460, 2737438404, 1112388844,
 ];
 
 pub const TEST_GUEST_ID: [u32; 8] = [
-    1139820909, 997563335, 2353495433, 3120987745, 583364796, 1182468441, 3901448634, 2610842315,
+    512798453, 987027531, 1784302714, 4250811124, 2339521530, 2655466852, 2639393939, 2542078329,
 ];
 
 pub fn get_compliance_id() -> Digest {
     Digest::from(crate::constants::COMPLIANCE_GUEST_ID)
 }
 
+pub fn get_multi_compliance_id() -> Digest {
+    Digest::from(MULTI_COMPLIANCE_GUEST_ID)
+}
+
+pub fn get_private_compliance_id() -> Digest {
+    Digest::from(PRIVATE_COMPLIANCE_GUEST_ID)
+}
+
+pub fn get_logic_wrapper_id() -> Digest {
+    Digest::from(LOGIC_WRAPPER_GUEST_ID)
+}
+
+pub fn get_aggregation_id() -> Digest {
+    Digest::from(AGGREGATION_GUEST_ID)
+}
+
 #[test]
 fn print_compliance_id() {
     println!("compliance_id: {:?}", get_compliance_id());
@@ -0,0 +1,1003 @@
+//! Canonical wire format of transactions.
+//!
+//! Binary encoding: a 7-byte header followed by the bincode 1.x encoding of
+//! the value with the default options (little-endian fixed-width integers,
+//! `u64` length prefixes, `u32` enum variant indices) of its
+//! [`WireFormat::Body`]. The body mirrors the value, except that receipts are
+//! byte strings in the receipt encoding below rather than risc0's own serde
+//! encoding, which changes between risc0 releases. The types themselves keep
+//! risc0's serde, so any receipt, e.g. a succinct one exchanged with a
+//! relayer, can still be serialized.
+//!
+//! | offset | size | content                                   |
+//! |--------|------|-------------------------------------------|
+//! | 0      | 4    | magic `AARM`                              |
+//! | 4      | 2    | format version, big-endian                |
+//! | 6      | 1    | kind, see [`WireKind`]                    |
+//! | 7      |      | bincode body                              |
+//!
+//! JSON encoding: digests and byte strings are `0x`-prefixed lowercase hex,
+//! quantities are decimal strings, and receipts are the hex of their receipt
+//! encoding. The version is included in the top-level object.
+//!
+//! Receipt encoding, versioned on its own by [`RECEIPT_VERSION`]:
+//!
+//! | offset | size | content                                        |
+//! |--------|------|------------------------------------------------|
+//! | 0      | 1    | receipt encoding version                       |
+//! | 1      | 1    | seal kind, see [`SealKind`]                    |
+//! | 2      | 32   | image ID                                       |
+//! | 34     | 4    | journal length `n`, big-endian                 |
+//! | 38     | n    | journal                                        |
+//! | 38 + n |      | Groth16 only: 32-byte verifier parameters      |
+//! |        |      | digest, 4-byte big-endian seal length, seal    |
+//!
+//! The claim is not encoded: only receipts of a successful execution
+//! without pending assumptions are encoded, whose claim is rebuilt from the
+//! image ID and the journal. Composite and succinct receipts have no wire
+//! encoding, transactions carry Groth16 receipts or, for testing, fake ones.
+
+use crate::{
+    action::{Action, ForwarderCalldata},
+    error::EncodingError,
+    logic_proof::LogicProof,
+    private_action::{PrivateAction, PrivateForwarderCall, PrivateLogicProof},
+    transaction::{Delta, Transaction},
+};
+use aarm_core::{
+    delta_proof::{DeltaProof, DeltaWitness},
+    nullifier_key::NullifierKeyCommitment,
+    resource::Resource,
+};
+use risc0_zkvm::{
+    sha::Digestible, Digest, FakeReceipt, Groth16Receipt, InnerReceipt, Receipt, ReceiptClaim,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+pub const WIRE_MAGIC: [u8; 4] = *b"AARM";
+pub const WIRE_VERSION: u16 = 1;
+const HEADER_LEN: usize = 7;
+
+pub const RECEIPT_VERSION: u8 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum SealKind {
+    Fake = 0,
+    Groth16 = 1,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum WireKind {
+    Transaction = 1,
+    Action = 2,
+    LogicProof = 3,
+    ForwarderCalldata = 4,
+}
+
+pub trait WireFormat: Sized {
+    const KIND: WireKind;
+    /// The JSON representation of the value.
+    type Json: Serialize + DeserializeOwned + TryInto<Self, Error = EncodingError>;
+    /// The bincode body of the binary encoding, with receipts in the
+    /// receipt encoding.
+    type Body: Serialize + DeserializeOwned;
+
+    fn to_json_value(&self) -> Result<Self::Json, EncodingError>;
+
+    fn to_body(&self) -> Result<Self::Body, EncodingError>;
+
+    fn from_body(body: Self::Body) -> Result<Self, EncodingError>;
+
+    fn to_wire_bytes(&self) -> Result<Vec<u8>, EncodingError> {
+        let mut bytes = Vec::from(WIRE_MAGIC);
+        bytes.extend_from_slice(&WIRE_VERSION.to_be_bytes());
+        bytes.push(Self::KIND as u8);
+        bytes.extend(
+            bincode::serialize(&self.to_body()?)
+                .map_err(|e| EncodingError::Bincode(e.to_string()))?,
+        );
+        Ok(bytes)
+    }
+
+    fn from_wire_bytes(bytes: &[u8]) -> Result<Self, EncodingError> {
+        if bytes.len() < HEADER_LEN || bytes[..4] != WIRE_MAGIC {
+            return Err(EncodingError::InvalidMagic);
+        }
+        let version = u16::from_be_bytes([bytes[4], bytes[5]]);
+        if version != WIRE_VERSION {
+            return Err(EncodingError::UnsupportedVersion(version));
+        }
+        if bytes[6] != Self::KIND as u8 {
+            return Err(EncodingError::KindMismatch {
+                expected: Self::KIND as u8,
+                found: bytes[6],
+            });
+        }
+        let body = bincode::deserialize(&bytes[HEADER_LEN..])
+            .map_err(|e| EncodingError::Bincode(e.to_string()))?;
+        Self::from_body(body)
+    }
+
+    fn to_json(&self) -> Result<String, EncodingError> {
+        Ok(serde_json::to_string_pretty(&self.to_json_value()?)
+            .expect("JSON serialization is infallible"))
+    }
+
+    fn from_json(json: &str) -> Result<Self, EncodingError> {
+        let value: Self::Json =
+            serde_json::from_str(json).map_err(|e| EncodingError::Json(e.to_string()))?;
+        value.try_into()
+    }
+}
+
+impl WireFormat for Transaction {
+    const KIND: WireKind = WireKind::Transaction;
+    type Json = TransactionJson;
+    type Body = TransactionBody;
+
+    fn to_json_value(&self) -> Result<TransactionJson, EncodingError> {
+        TransactionJson::try_from(self)
+    }
+
+    fn to_body(&self) -> Result<TransactionBody, EncodingError> {
+        TransactionBody::try_from(self)
+    }
+
+    fn from_body(body: TransactionBody) -> Result<Self, EncodingError> {
+        body.try_into()
+    }
+}
+
+impl WireFormat for Action {
+    const KIND: WireKind = WireKind::Action;
+    type Json = ActionJson;
+    type Body = ActionBody;
+
+    fn to_json_value(&self) -> Result<ActionJson, EncodingError> {
+        ActionJson::try_from(self)
+    }
+
+    fn to_body(&self) -> Result<ActionBody, EncodingError> {
+        ActionBody::try_from(self)
+    }
+
+    fn from_body(body: ActionBody) -> Result<Self, EncodingError> {
+        body.try_into()
+    }
+}
+
+impl WireFormat for LogicProof {
+    const KIND: WireKind = WireKind::LogicProof;
+    type Json = LogicProofJson;
+    type Body = LogicProofBody;
+
+    fn to_json_value(&self) -> Result<LogicProofJson, EncodingError> {
+        LogicProofJson::try_from(self)
+    }
+
+    fn to_body(&self) -> Result<LogicProofBody, EncodingError> {
+        LogicProofBody::try_from(self)
+    }
+
+    fn from_body(body: LogicProofBody) -> Result<Self, EncodingError> {
+        body.try_into()
+    }
+}
+
+impl WireFormat for ForwarderCalldata {
+    const KIND: WireKind = WireKind::ForwarderCalldata;
+    type Json = ForwarderCalldataJson;
+    type Body = ForwarderCalldata;
+
+    fn to_json_value(&self) -> Result<ForwarderCalldataJson, EncodingError> {
+        Ok(ForwarderCalldataJson::from(self))
+    }
+
+    fn to_body(&self) -> Result<ForwarderCalldata, EncodingError> {
+        Ok(self.clone())
+    }
+
+    fn from_body(body: ForwarderCalldata) -> Result<Self, EncodingError> {
+        Ok(body)
+    }
+}
+
+/// The bincode body of a transaction, see [`WireFormat::Body`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TransactionBody {
+    pub actions: Vec<ActionBody>,
+    pub private_actions: Vec<PrivateActionBody>,
+    pub delta_proof: Delta,
+    pub aggregation_proof: Option<Vec<u8>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ActionBody {
+    pub compliance_units: Vec<Vec<u8>>,
+    pub multi_compliance_units: Vec<Vec<u8>>,
+    pub logic_proofs: Vec<LogicProofBody>,
+    pub resource_forwarder_calldata_pairs: Vec<(Resource, ForwarderCalldata)>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PrivateActionBody {
+    pub compliance_units: Vec<Vec<u8>>,
+    pub logic_proofs: Vec<Vec<u8>>,
+    pub forwarder_calls: Vec<PrivateForwarderCall>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LogicProofBody {
+    pub receipt: Vec<u8>,
+    pub verifying_key: Digest,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionJson {
+    pub version: u16,
+    pub actions: Vec<ActionJson>,
+    #[serde(default)]
+    pub private_actions: Vec<PrivateActionJson>,
+    pub delta_proof: DeltaJson,
+    pub aggregation_proof: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeltaJson {
+    Witness(String),
+    Proof(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActionJson {
+    pub compliance_units: Vec<String>,
+    pub multi_compliance_units: Vec<String>,
+    pub logic_proofs: Vec<LogicProofJson>,
+    pub resource_forwarder_calldata_pairs: Vec<ResourceCalldataJson>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrivateActionJson {
+    pub compliance_units: Vec<String>,
+    pub logic_proofs: Vec<String>,
+    pub forwarder_calls: Vec<PrivateForwarderCallJson>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrivateForwarderCallJson {
+    pub resource: ResourceJson,
+    pub logic_cm_r: String,
+    pub calldata: ForwarderCalldataJson,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogicProofJson {
+    pub receipt: String,
+    pub verifying_key: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceCalldataJson {
+    pub resource: ResourceJson,
+    pub calldata: ForwarderCalldataJson,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceJson {
+    pub logic_ref: String,
+    pub label_ref: String,
+    pub quantity: String,
+    pub value_ref: String,
+    pub is_ephemeral: bool,
+    pub nonce: String,
+    pub nk_commitment: String,
+    pub rand_seed: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForwarderCalldataJson {
+    pub untrusted_forwarder: String,
+    pub input: String,
+    pub output: String,
+}
+
+impl TryFrom<&Transaction> for TransactionBody {
+    type Error = EncodingError;
+
+    fn try_from(tx: &Transaction) -> Result<Self, Self::Error> {
+        Ok(TransactionBody {
+            actions: tx
+                .actions
+                .iter()
+                .map(ActionBody::try_from)
+                .collect::<Result<_, _>>()?,
+            private_actions: tx
+                .private_actions
+                .iter()
+                .map(PrivateActionBody::try_from)
+                .collect::<Result<_, _>>()?,
+            delta_proof: tx.delta_proof.clone(),
+            aggregation_proof: tx
+                .aggregation_proof
+                .as_ref()
+                .map(encode_receipt)
+                .transpose()?,
+        })
+    }
+}
+
+impl TryFrom<TransactionBody> for Transaction {
+    type Error = EncodingError;
+
+    fn try_from(body: TransactionBody) -> Result<Self, Self::Error> {
+        let mut tx = Transaction::new(
+            body.actions
+                .into_iter()
+                .map(Action::try_from)
+                .collect::<Result<_, _>>()?,
+            body.delta_proof,
+        )
+        .with_private_actions(
+            body.private_actions
+                .into_iter()
+                .map(PrivateAction::try_from)
+                .collect::<Result<_, _>>()?,
+        );
+        tx.aggregation_proof = body
+            .aggregation_proof
+            .as_deref()
+            .map(decode_receipt)
+            .transpose()?;
+        Ok(tx)
+    }
+}
+
+impl TryFrom<&Action> for ActionBody {
+    type Error = EncodingError;
+
+    fn try_from(action: &Action) -> Result<Self, Self::Error> {
+        Ok(ActionBody {
+            compliance_units: encode_receipts(&action.compliance_units)?,
+            multi_compliance_units: encode_receipts(&action.multi_compliance_units)?,
+            logic_proofs: action
+                .logic_proofs
+                .iter()
+                .map(LogicProofBody::try_from)
+                .collect::<Result<_, _>>()?,
+            resource_forwarder_calldata_pairs: action.resource_forwarder_calldata_pairs.clone(),
+        })
+    }
+}
+
+impl TryFrom<ActionBody> for Action {
+    type Error = EncodingError;
+
+    fn try_from(body: ActionBody) -> Result<Self, Self::Error> {
+        let logic_proofs = body
+            .logic_proofs
+            .into_iter()
+            .map(LogicProof::try_from)
+            .collect::<Result<_, _>>()?;
+        Ok(Action::new(
+            decode_receipts(&body.compliance_units)?,
+            logic_proofs,
+            body.resource_forwarder_calldata_pairs,
+        )
+        .with_multi_compliance_units(decode_receipts(&body.multi_compliance_units)?))
+    }
+}
+
+impl TryFrom<&PrivateAction> for PrivateActionBody {
+    type Error = EncodingError;
+
+    fn try_from(action: &PrivateAction) -> Result<Self, Self::Error> {
+        Ok(PrivateActionBody {
+            compliance_units: encode_receipts(&action.compliance_units)?,
+            logic_proofs: action
+                .logic_proofs
+                .iter()
+                .map(|proof| encode_receipt(&proof.receipt))
+                .collect::<Result<_, _>>()?,
+            forwarder_calls: action.forwarder_calls.clone(),
+        })
+    }
+}
+
+impl TryFrom<PrivateActionBody> for PrivateAction {
+    type Error = EncodingError;
+
+    fn try_from(body: PrivateActionBody) -> Result<Self, Self::Error> {
+        let logic_proofs = decode_receipts(&body.logic_proofs)?
+            .into_iter()
+            .map(|receipt| PrivateLogicProof { receipt })
+            .collect();
+        Ok(PrivateAction::new(
+            decode_receipts(&body.compliance_units)?,
+            logic_proofs,
+            body.forwarder_calls,
+        ))
+    }
+}
+
+impl TryFrom<&LogicProof> for LogicProofBody {
+    type Error = EncodingError;
+
+    fn try_from(proof: &LogicProof) -> Result<Self, Self::Error> {
+        Ok(LogicProofBody {
+            receipt: encode_receipt(&proof.receipt)?,
+            verifying_key: proof.verifying_key,
+        })
+    }
+}
+
+impl TryFrom<LogicProofBody> for LogicProof {
+    type Error = EncodingError;
+
+    fn try_from(body: LogicProofBody) -> Result<Self, Self::Error> {
+        Ok(LogicProof {
+            receipt: decode_receipt(&body.receipt)?,
+            verifying_key: body.verifying_key,
+        })
+    }
+}
+
+impl TryFrom<&Transaction> for TransactionJson {
+    type Error = EncodingError;
+
+    fn try_from(tx: &Transaction) -> Result<Self, Self::Error> {
+        Ok(TransactionJson {
+            version: WIRE_VERSION,
+            actions: tx
+                .actions
+                .iter()
+                .map(ActionJson::try_from)
+                .collect::<Result<_, _>>()?,
+            private_actions: tx
+                .private_actions
+                .iter()
+                .map(PrivateActionJson::try_from)
+                .collect::<Result<_, _>>()?,
+            delta_proof: match &tx.delta_proof {
+                Delta::Witness(witness) => DeltaJson::Witness(to_hex(witness.to_bytes())),
+                Delta::Proof(proof) => DeltaJson::Proof(to_hex(proof.to_bytes())),
+            },
+            aggregation_proof: tx
+                .aggregation_proof
+                .as_ref()
+                .map(receipt_to_hex)
+                .transpose()?,
+        })
+    }
+}
+
+impl TryFrom<TransactionJson> for Transaction {
+    type Error = EncodingError;
+
+    fn try_from(json: TransactionJson) -> Result<Self, Self::Error> {
+        if json.version != WIRE_VERSION {
+            return Err(EncodingError::UnsupportedVersion(json.version));
+        }
+        let delta = match json.delta_proof {
+            DeltaJson::Witness(hex) => {
+                let bytes: [u8; 32] = from_hex_array(&hex)?;
+                Delta::Witness(
+                    DeltaWitness::try_from(&bytes[..])
+                        .map_err(|e| EncodingError::Invalid(format!("delta witness: {e}")))?,
+                )
+            }
+            DeltaJson::Proof(hex) => {
+                let bytes: [u8; 65] = from_hex_array(&hex)?;
+                Delta::Proof(
+                    DeltaProof::try_from(&bytes[..])
+                        .map_err(|e| EncodingError::Invalid(format!("delta proof: {e}")))?,
+                )
+            }
+        };
+        let mut tx = Transaction::new(
+            json.actions
+                .into_iter()
+                .map(Action::try_from)
+                .collect::<Result<_, _>>()?,
+            delta,
+        )
+        .with_private_actions(
+            json.private_actions
+                .into_iter()
+                .map(PrivateAction::try_from)
+                .collect::<Result<_, _>>()?,
+        );
+        tx.aggregation_proof = json
+            .aggregation_proof
+            .as_deref()
+            .map(receipt_from_hex)
+            .transpose()?;
+        Ok(tx)
+    }
+}
+
+impl TryFrom<&Action> for ActionJson {
+    type Error = EncodingError;
+
+    fn try_from(action: &Action) -> Result<Self, Self::Error> {
+        Ok(ActionJson {
+            compliance_units: action
+                .compliance_units
+                .iter()
+                .map(receipt_to_hex)
+                .collect::<Result<_, _>>()?,
+            multi_compliance_units: action
+                .multi_compliance_units
+                .iter()
+                .map(receipt_to_hex)
+                .collect::<Result<_, _>>()?,
+            logic_proofs: action
+                .logic_proofs
+                .iter()
+                .map(LogicProofJson::try_from)
+                .collect::<Result<_, _>>()?,
+            resource_forwarder_calldata_pairs: action
+                .resource_forwarder_calldata_pairs
+                .iter()
+                .map(|(resource, calldata)| ResourceCalldataJson {
+                    resource: ResourceJson::from(resource),
+                    calldata: ForwarderCalldataJson::from(calldata),
+                })
+                .collect(),
+        })
+    }
+}
+
+impl TryFrom<ActionJson> for Action {
+    type Error = EncodingError;
+
+    fn try_from(json: ActionJson) -> Result<Self, Self::Error> {
+        let compliance_units = json
+            .compliance_units
+            .iter()
+            .map(|hex| receipt_from_hex(hex))
+            .collect::<Result<_, _>>()?;
+        let multi_compliance_units = json
+            .multi_compliance_units
+            .iter()
+            .map(|hex| receipt_from_hex(hex))
+            .collect::<Result<_, _>>()?;
+        let logic_proofs = json
+            .logic_proofs
+            .into_iter()
+            .map(LogicProof::try_from)
+            .collect::<Result<_, _>>()?;
+        let resource_forwarder_calldata_pairs = json
+            .resource_forwarder_calldata_pairs
+            .into_iter()
+            .map(|pair| {
+                Ok((
+                    Resource::try_from(pair.resource)?,
+                    ForwarderCalldata::try_from(pair.calldata)?,
+                ))
+            })
+            .collect::<Result<_, EncodingError>>()?;
+        Ok(Action::new(
+            compliance_units,
+            logic_proofs,
+            resource_forwarder_calldata_pairs,
+        )
+        .with_multi_compliance_units(multi_compliance_units))
+    }
+}
+
+impl TryFrom<&PrivateAction> for PrivateActionJson {
+    type Error = EncodingError;
+
+    fn try_from(action: &PrivateAction) -> Result<Self, Self::Error> {
+        Ok(PrivateActionJson {
+            compliance_units: action
+                .compliance_units
+                .iter()
+                .map(receipt_to_hex)
+                .collect::<Result<_, _>>()?,
+            logic_proofs: action
+                .logic_proofs
+                .iter()
+                .map(|proof| receipt_to_hex(&proof.receipt))
+                .collect::<Result<_, _>>()?,
+            forwarder_calls: action
+                .forwarder_calls
+                .iter()
+                .map(|call| PrivateForwarderCallJson {
+                    resource: ResourceJson::from(&call.resource),
+                    logic_cm_r: to_hex(call.logic_cm_r),
+                    calldata: ForwarderCalldataJson::from(&call.calldata),
+                })
+                .collect(),
+        })
+    }
+}
+
+impl TryFrom<PrivateActionJson> for PrivateAction {
+    type Error = EncodingError;
+
+    fn try_from(json: PrivateActionJson) -> Result<Self, Self::Error> {
+        let compliance_units = json
+            .compliance_units
+            .iter()
+            .map(|hex| receipt_from_hex(hex))
+            .collect::<Result<_, _>>()?;
+        let logic_proofs = json
+            .logic_proofs
+            .iter()
+            .map(|hex| {
+                Ok(PrivateLogicProof {
+                    receipt: receipt_from_hex(hex)?,
+                })
+            })
+            .collect::<Result<_, EncodingError>>()?;
+        let forwarder_calls = json
+            .forwarder_calls
+            .into_iter()
+            .map(|call| {
+                Ok(PrivateForwarderCall {
+                    resource: Resource::try_from(call.resource)?,
+                    logic_cm_r: from_hex_array(&call.logic_cm_r)?,
+                    calldata: ForwarderCalldata::try_from(call.calldata)?,
+                })
+            })
+            .collect::<Result<_, EncodingError>>()?;
+        Ok(PrivateAction::new(
+            compliance_units,
+            logic_proofs,
+            forwarder_calls,
+        ))
+    }
+}
+
+impl TryFrom<&LogicProof> for LogicProofJson {
+    type Error = EncodingError;
+
+    fn try_from(proof: &LogicProof) -> Result<Self, Self::Error> {
+        Ok(LogicProofJson {
+            receipt: receipt_to_hex(&proof.receipt)?,
+            verifying_key: to_hex(proof.verifying_key),
+        })
+    }
+}
+
+impl TryFrom<LogicProofJson> for LogicProof {
+    type Error = EncodingError;
+
+    fn try_from(json: LogicProofJson) -> Result<Self, Self::Error> {
+        Ok(LogicProof {
+            receipt: receipt_from_hex(&json.receipt)?,
+            verifying_key: digest_from_hex(&json.verifying_key)?,
+        })
+    }
+}
+
+impl From<&Resource> for ResourceJson {
+    fn from(resource: &Resource) -> Self {
+        ResourceJson {
+            logic_ref: to_hex(resource.logic_ref),
+            label_ref: to_hex(resource.label_ref),
+            quantity: resource.quantity.to_string(),
+            value_ref: to_hex(resource.value_ref),
+            is_ephemeral: resource.is_ephemeral,
+            nonce: to_hex(resource.nonce),
+            nk_commitment: to_hex(resource.nk_commitment.inner()),
+            rand_seed: to_hex(resource.rand_seed),
+        }
+    }
+}
+
+impl TryFrom<ResourceJson> for Resource {
+    type Error = EncodingError;
+
+    fn try_from(json: ResourceJson) -> Result<Self, Self::Error> {
+        Ok(Resource {
+            logic_ref: digest_from_hex(&json.logic_ref)?,
+            label_ref: digest_from_hex(&json.label_ref)?,
+            quantity: json
+                .quantity
+                .parse()
+                .map_err(|_| EncodingError::Invalid(format!("quantity {}", json.quantity)))?,
+            value_ref: digest_from_hex(&json.value_ref)?,
+            is_ephemeral: json.is_ephemeral,
+            nonce: from_hex_array(&json.nonce)?,
+            nk_commitment: NullifierKeyCommitment::from_bytes(from_hex_array(&json.nk_commitment)?),
+            rand_seed: from_hex_array(&json.rand_seed)?,
+        })
+    }
+}
+
+impl From<&ForwarderCalldata> for ForwarderCalldataJson {
+    fn from(calldata: &ForwarderCalldata) -> Self {
+        ForwarderCalldataJson {
+            untrusted_forwarder: to_hex(calldata.untrusted_forwarder),
+            input: to_hex(&calldata.input),
+            output: to_hex(&calldata.output),
+        }
+    }
+}
+
+impl TryFrom<ForwarderCalldataJson> for ForwarderCalldata {
+    type Error = EncodingError;
+
+    fn try_from(json: ForwarderCalldataJson) -> Result<Self, Self::Error> {
+        Ok(ForwarderCalldata {
+            untrusted_forwarder: from_hex_array(&json.untrusted_forwarder)?,
+            input: from_hex(&json.input)?,
+            output: from_hex(&json.output)?,
+        })
+    }
+}
+
+fn to_hex(bytes: impl AsRef<[u8]>) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn from_hex(s: &str) -> Result<Vec<u8>, EncodingError> {
+    let digits = s
+        .strip_prefix("0x")
+        .ok_or_else(|| EncodingError::Hex(format!("missing 0x prefix in {s}")))?;
+    hex::decode(digits).map_err(|e| EncodingError::Hex(e.to_string()))
+}
+
+fn from_hex_array<const N: usize>(s: &str) -> Result<[u8; N], EncodingError> {
+    let bytes = from_hex(s)?;
+    bytes.try_into().map_err(|bytes: Vec<u8>| {
+        EncodingError::Hex(format!("expected {N} bytes, got {}", bytes.len()))
+    })
+}
+
+fn digest_from_hex(s: &str) -> Result<Digest, EncodingError> {
+    from_hex_array::<32>(s).map(Digest::from_bytes)
+}
+
+fn receipt_to_hex(receipt: &Receipt) -> Result<String, EncodingError> {
+    encode_receipt(receipt).map(to_hex)
+}
+
+fn receipt_from_hex(s: &str) -> Result<Receipt, EncodingError> {
+    decode_receipt(&from_hex(s)?)
+}
+
+fn encode_receipts(receipts: &[Receipt]) -> Result<Vec<Vec<u8>>, EncodingError> {
+    receipts.iter().map(encode_receipt).collect()
+}
+
+fn decode_receipts(receipts: &[Vec<u8>]) -> Result<Vec<Receipt>, EncodingError> {
+    receipts.iter().map(|bytes| decode_receipt(bytes)).collect()
+}
+
+/// Encodes a receipt in the receipt encoding described in the module doc.
+pub fn encode_receipt(receipt: &Receipt) -> Result<Vec<u8>, EncodingError> {
+    let kind = match &receipt.inner {
+        InnerReceipt::Fake(_) => SealKind::Fake,
+        InnerReceipt::Groth16(_) => SealKind::Groth16,
+        _ => {
+            return Err(EncodingError::UnsupportedReceipt(
+                "only Groth16 and fake receipts can be encoded".to_string(),
+            ))
+        }
+    };
+    let claim = receipt
+        .claim()
+        .ok()
+        .and_then(|claim| claim.value().ok())
+        .ok_or_else(|| EncodingError::UnsupportedReceipt("pruned claim".to_string()))?;
+    let image_id = claim.pre.digest();
+    let journal = &receipt.journal.bytes;
+    if claim.digest() != ReceiptClaim::ok(image_id, journal.clone()).digest() {
+        return Err(EncodingError::UnsupportedReceipt(
+            "not a successful execution".to_string(),
+        ));
+    }
+
+    let mut bytes = vec![RECEIPT_VERSION, kind as u8];
+    bytes.extend_from_slice(image_id.as_bytes());
+    put_bytes(&mut bytes, journal)?;
+    if let InnerReceipt::Groth16(inner) = &receipt.inner {
+        bytes.extend_from_slice(inner.verifier_parameters.as_bytes());
+        put_bytes(&mut bytes, &inner.seal)?;
+    }
+    Ok(bytes)
+}
+
+/// Decodes a receipt encoded with [`encode_receipt`].
+pub fn decode_receipt(mut bytes: &[u8]) -> Result<Receipt, EncodingError> {
+    let [version, kind] = take_array(&mut bytes)?;
+    if version != RECEIPT_VERSION {
+        return Err(EncodingError::UnsupportedReceipt(format!(
+            "encoding version {version}"
+        )));
+    }
+    let image_id = Digest::from_bytes(take_array(&mut bytes)?);
+    let journal = take_bytes(&mut bytes)?;
+    let claim = ReceiptClaim::ok(image_id, journal.clone());
+    let inner = if kind == SealKind::Fake as u8 {
+        InnerReceipt::Fake(FakeReceipt::new(claim))
+    } else if kind == SealKind::Groth16 as u8 {
+        let verifier_parameters = Digest::from_bytes(take_array(&mut bytes)?);
+        let seal = take_bytes(&mut bytes)?;
+        InnerReceipt::Groth16(Groth16Receipt::new(seal, claim.into(), verifier_parameters))
+    } else {
+        return Err(EncodingError::UnsupportedReceipt(format!(
+            "seal kind {kind}"
+        )));
+    };
+    if !bytes.is_empty() {
+        return Err(EncodingError::Invalid(
+            "receipt: trailing bytes".to_string(),
+        ));
+    }
+    Ok(Receipt::new(inner, journal))
+}
+
+fn put_bytes(bytes: &mut Vec<u8>, value: &[u8]) -> Result<(), EncodingError> {
+    let len = u32::try_from(value.len())
+        .map_err(|_| EncodingError::Invalid(format!("receipt: field of {} bytes", value.len())))?;
+    bytes.extend_from_slice(&len.to_be_bytes());
+    bytes.extend_from_slice(value);
+    Ok(())
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], EncodingError> {
+    if bytes.len() < len {
+        return Err(EncodingError::Invalid("receipt: truncated".to_string()));
+    }
+    let (head, tail) = bytes.split_at(len);
+    *bytes = tail;
+    Ok(head)
+}
+
+fn take_array<const N: usize>(bytes: &mut &[u8]) -> Result<[u8; N], EncodingError> {
+    Ok(take(bytes, N)?.try_into().unwrap())
+}
+
+fn take_bytes(bytes: &mut &[u8]) -> Result<Vec<u8>, EncodingError> {
+    let len = u32::from_be_bytes(take_array(bytes)?);
+    Ok(take(bytes, len as usize)?.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aarm_core::delta_proof::DeltaWitness;
+    use k256::Scalar;
+    use risc0_zkvm::CompositeReceipt;
+
+    fn fake_receipt(image_id: [u32; 8], journal: Vec<u8>) -> Receipt {
+        let claim = ReceiptClaim::ok(Digest::from(image_id), journal.clone());
+        Receipt::new(InnerReceipt::Fake(FakeReceipt::new(claim)), journal)
+    }
+
+    // A deterministic transaction covering every field of the format
+    fn golden_transaction() -> Transaction {
+        let resource = Resource {
+            logic_ref: Digest::from([1u32; 8]),
+            label_ref: Digest::from([2u32; 8]),
+            quantity: 1 << 100,
+            value_ref: Digest::from([3u32; 8]),
+            is_ephemeral: true,
+            nonce: [4u8; 32],
+            nk_commitment: NullifierKeyCommitment::from_bytes([5u8; 32]),
+            rand_seed: [6u8; 32],
+        };
+        let calldata = ForwarderCalldata {
+            untrusted_forwarder: [7u8; 20],
+            input: vec![8, 9],
+            output: vec![10],
+        };
+        let logic_proof = LogicProof {
+            receipt: fake_receipt([11u32; 8], vec![12, 13, 14, 15]),
+            verifying_key: Digest::from([11u32; 8]),
+        };
+        let action = Action::new(
+            vec![fake_receipt([16u32; 8], vec![17, 18, 19, 20])],
+            vec![logic_proof],
+            vec![(resource, calldata)],
+        );
+        let witness = DeltaWitness::from_scalars(&[Scalar::ONE]);
+        let proof = DeltaProof::prove(b"golden", &witness);
+        Transaction::new(vec![action], Delta::Proof(proof))
+    }
+
+    #[test]
+    fn test_wire_round_trip() {
+        let tx = golden_transaction();
+        let bytes = tx.to_wire_bytes().unwrap();
+        assert_eq!(&bytes[..7], b"AARM\x00\x01\x01");
+        let decoded = Transaction::from_wire_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_wire_bytes().unwrap(), bytes);
+
+        let json = tx.to_json().unwrap();
+        let decoded = Transaction::from_json(&json).unwrap();
+        assert_eq!(decoded.to_json().unwrap(), json);
+        assert_eq!(decoded.to_wire_bytes().unwrap(), bytes);
+
+        let action = &tx.actions[0];
+        assert!(matches!(
+            Transaction::from_wire_bytes(&action.to_wire_bytes().unwrap()),
+            Err(EncodingError::KindMismatch {
+                expected: 1,
+                found: 2
+            })
+        ));
+        let mut future = bytes.clone();
+        future[5] = 2;
+        assert_eq!(
+            Transaction::from_wire_bytes(&future).unwrap_err(),
+            EncodingError::UnsupportedVersion(2)
+        );
+    }
+
+    #[test]
+    fn test_serde_keeps_any_receipt() {
+        // An empty composite receipt: two empty vectors and a zero digest
+        let composite: CompositeReceipt = bincode::deserialize(&[0u8; 48]).unwrap();
+        let mut tx = golden_transaction();
+        tx.aggregation_proof = Some(Receipt::new(InnerReceipt::Composite(composite), vec![]));
+
+        let decoded: Transaction = bincode::deserialize(&bincode::serialize(&tx).unwrap()).unwrap();
+        assert!(matches!(
+            decoded.aggregation_proof.unwrap().inner,
+            InnerReceipt::Composite(_)
+        ));
+        assert!(matches!(
+            tx.to_wire_bytes(),
+            Err(EncodingError::UnsupportedReceipt(_))
+        ));
+        assert!(matches!(
+            tx.to_json(),
+            Err(EncodingError::UnsupportedReceipt(_))
+        ));
+    }
+
+    #[test]
+    fn test_receipt_encoding() {
+        let journal = vec![1, 2, 3];
+        let claim = ReceiptClaim::ok(Digest::from([4u32; 8]), journal.clone());
+        let groth16 = Receipt::new(
+            InnerReceipt::Groth16(Groth16Receipt::new(
+                vec![5; 256],
+                claim.into(),
+                Digest::from([6u32; 8]),
+            )),
+            journal,
+        );
+        let bytes = encode_receipt(&groth16).unwrap();
+        assert_eq!(&bytes[..2], &[RECEIPT_VERSION, SealKind::Groth16 as u8]);
+        assert_eq!(bytes.len(), 2 + 32 + 4 + 3 + 32 + 4 + 256);
+        let decoded = decode_receipt(&bytes).unwrap();
+        assert_eq!(
+            decoded.claim().unwrap().digest(),
+            groth16.claim().unwrap().digest()
+        );
+        assert_eq!(encode_receipt(&decoded).unwrap(), bytes);
+
+        let fake = fake_receipt([7u32; 8], vec![8]);
+        let bytes = encode_receipt(&fake).unwrap();
+        assert_eq!(decode_receipt(&bytes).unwrap().journal, fake.journal);
+        assert!(decode_receipt(&bytes[..bytes.len() - 1]).is_err());
+        let mut future = bytes.clone();
+        future[0] = 2;
+        assert!(matches!(
+            decode_receipt(&future),
+            Err(EncodingError::UnsupportedReceipt(_))
+        ));
+    }
+
+    #[test]
+    fn test_golden_vectors() {
+        let tx = golden_transaction();
+        assert_eq!(
+            hex::encode(tx.to_wire_bytes().unwrap()),
+            include_str!("../testdata/transaction_v1.hex").trim()
+        );
+        assert_eq!(
+            tx.to_json().unwrap(),
+            include_str!("../testdata/transaction_v1.json").trim()
+        );
+    }
+}
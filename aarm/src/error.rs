@@ -0,0 +1,132 @@
+use crate::balance::ResourceKind;
+use aarm_core::action_tree::ActionTreeError;
+use risc0_zkvm::Digest;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum VerificationError {
+    #[error("invalid compliance receipt at index {0}")]
+    InvalidComplianceReceipt(usize),
+    #[error("invalid multi compliance receipt at index {0}")]
+    InvalidMultiComplianceReceipt(usize),
+    #[error("invalid logic receipt at index {0}")]
+    InvalidLogicReceipt(usize),
+    #[error(transparent)]
+    ActionTree(#[from] ActionTreeError),
+    #[error("action tree root mismatch for logic proof {0}")]
+    RootMismatch(usize),
+    #[error("action tree depth mismatch for logic proof {0}")]
+    ActionTreeDepthMismatch(usize),
+    #[error("tag of logic proof {0} not found in the action tree")]
+    TagNotFound(usize),
+    #[error("verifying key of logic proof {0} does not match the logic ref")]
+    VerifyingKeyMismatch(usize),
+    #[error("logic proof {0} does not match whether its tag is a nullifier")]
+    ConsumedFlagMismatch(usize),
+    #[error("logic proof {0} proves a tag that already has a logic proof")]
+    DuplicateLogicProof(usize),
+    #[error("no logic proof for tag {0}")]
+    MissingLogicProof(Digest),
+    #[error("nullifier {0} is consumed twice")]
+    DuplicateNullifier(Digest),
+    #[error("commitment {0} is created twice")]
+    DuplicateCommitment(Digest),
+    #[error("tag {0} appears twice in the action tree")]
+    DuplicateTag(Digest),
+    #[error("logic commitment of logic proof {0} does not match the compliance instance")]
+    LogicCommitmentMismatch(usize),
+    #[error("forwarder call {0} is not bound in the app data of a logic proof")]
+    ForwarderCallNotBound(usize),
+    #[error("resource of forwarder call {0} is not the resource of its logic proof")]
+    ForwarderResourceMismatch(usize),
+    #[error("failed to decode journal: {0}")]
+    JournalDecode(String),
+    #[error("invalid aggregation receipt")]
+    InvalidAggregationReceipt,
+    #[error("aggregation proof is missing")]
+    MissingAggregationProof,
+    #[error("aggregation proof was made for another compliance circuit")]
+    AggregationComplianceMismatch,
+    #[error("action {0} does not match the aggregation proof")]
+    AggregationActionMismatch(usize),
+    #[error("delta proof verification failed")]
+    DeltaProofFailed,
+    #[error("delta proof is missing")]
+    MissingDeltaProof,
+    #[error("length {0} does not fit the u32 length prefix of IDs")]
+    IdLengthOverflow(usize),
+}
+
+#[derive(Debug, Error)]
+pub enum ProvingError {
+    #[error("failed to serialize witness: {0}")]
+    WitnessSerialization(String),
+    /// The executor or prover failed, including guest panics on unsatisfied
+    /// constraints.
+    #[error("prover failed: {0}")]
+    Prover(#[source] anyhow::Error),
+    #[error("nullifier key does not match the resource")]
+    InvalidNullifierKey,
+    #[error(transparent)]
+    ActionTree(#[from] ActionTreeError),
+    #[error(
+        "logic circuits take action tree paths of depth {expected}, the tree has depth {found}"
+    )]
+    ActionTreeDepth { expected: usize, found: usize },
+    #[error("proved action does not verify: {0}")]
+    Verification(#[from] VerificationError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum LedgerError {
+    #[error("transaction verification failed: {0}")]
+    Verification(#[from] VerificationError),
+    #[error("nullifier {0} has already been spent")]
+    DoubleSpend(Digest),
+    #[error("unknown commitment tree root {0}")]
+    UnknownRoot(Digest),
+    #[error("commitment tree is full")]
+    CommitmentTreeFull,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BalanceError {
+    #[error("quantity of {0:?} overflows u128")]
+    QuantityOverflow(ResourceKind),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CompositionError {
+    #[error("cannot compose transactions with different delta types")]
+    DeltaMismatch,
+    #[error("nullifier {0} is consumed by both transactions")]
+    DuplicateNullifier(Digest),
+    #[error("commitment {0} is created by both transactions")]
+    DuplicateCommitment(Digest),
+    #[error("failed to read the transactions: {0}")]
+    Verification(#[from] VerificationError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum EncodingError {
+    #[error("missing AARM magic header")]
+    InvalidMagic,
+    #[error("unsupported wire format version {0}")]
+    UnsupportedVersion(u16),
+    #[error("expected wire kind {expected}, found {found}")]
+    KindMismatch { expected: u8, found: u8 },
+    #[error("unsupported receipt: {0}")]
+    UnsupportedReceipt(String),
+    #[error("invalid bincode body: {0}")]
+    Bincode(String),
+    #[error("invalid JSON: {0}")]
+    Json(String),
+    #[error("invalid hex: {0}")]
+    Hex(String),
+    #[error("invalid {0}")]
+    Invalid(String),
+    #[error("invalid ABI encoding: {0}")]
+    Abi(String),
+    #[error("cannot encode for the EVM: {0}")]
+    Evm(String),
+}
@@ -0,0 +1,470 @@
+//! EVM ABI encoding of transactions for the protocol adapter contract.
+//!
+//! The calldata of `execute(Transaction)` is encoded with `alloy-sol-types`
+//! from the Solidity declarations in `abi/IProtocolAdapter.sol`. They are
+//! this crate's declaration of the adapter interface, not generated from a
+//! deployed contract: the selector and the calldata vector in
+//! `testdata/evm_calldata_v1.hex` pin the encoding of that interface, and an
+//! adapter decodes the calldata only if its ABI matches the file.
+//!
+//! Proofs are RISC Zero seals as expected by the verifier router: the first
+//! 4 bytes of the verifier parameters digest followed by the Groth16 seal.
+//! Fake receipts are encoded for the mock verifier, as `0xffffffff` followed
+//! by the claim digest. Journals are not sent as such: the contract rebuilds
+//! each journal as the `risc0_zkvm::serde` encoding of its instance, and
+//! the claim from the image ID and the journal.
+//!
+//! The contract recovers the transaction delta from the ECDSA delta proof,
+//! so transactions with a `DeltaProof::Zero` are rejected. It verifies each
+//! proof on its own, so transactions with an aggregation proof, multi
+//! compliance units or private actions are rejected too.
+
+use crate::{
+    action::{Action, ForwarderCalldata},
+    error::EncodingError,
+    logic_proof::LogicProof,
+    transaction::{Delta, Transaction},
+};
+use aarm_core::{
+    compliance::ComplianceInstance,
+    delta_proof::DeltaProof,
+    logic_instance::{ExpirableBlob, LogicInstance},
+    nullifier_key::NullifierKeyCommitment,
+    resource::Resource,
+};
+use alloy_primitives::{Address, Bytes, B256, U256};
+use alloy_sol_types::SolCall;
+use risc0_zkvm::{sha::Digestible, Digest, InnerReceipt, Receipt};
+
+const FAKE_SELECTOR: [u8; 4] = [0xff; 4];
+
+/// The Solidity types of the protocol adapter, generated from
+/// `abi/IProtocolAdapter.sol`.
+pub mod abi {
+    alloy_sol_types::sol!(
+        #[sol(all_derives)]
+        "abi/IProtocolAdapter.sol"
+    );
+
+    pub use IProtocolAdapter::executeCall;
+}
+
+impl Transaction {
+    /// Encodes the calldata of `execute(Transaction)` on the protocol
+    /// adapter. The receipts must be Groth16 or, for testing, fake.
+    pub fn to_evm_calldata(&self) -> Result<Vec<u8>, EncodingError> {
+        let transaction = abi::Transaction::try_from(self)?;
+        Ok(abi::executeCall { transaction }.abi_encode())
+    }
+}
+
+/// Decodes the calldata of `execute(Transaction)`, checking the selector.
+pub fn decode_evm_calldata(calldata: &[u8]) -> Result<abi::Transaction, EncodingError> {
+    abi::executeCall::abi_decode(calldata, true)
+        .map(|call| call.transaction)
+        .map_err(|e| EncodingError::Abi(e.to_string()))
+}
+
+impl TryFrom<&Transaction> for abi::Transaction {
+    type Error = EncodingError;
+
+    fn try_from(tx: &Transaction) -> Result<Self, Self::Error> {
+        let delta_proof = match &tx.delta_proof {
+            // The adapter recovers the delta from the signature, which a zero
+            // proof does not carry
+            Delta::Proof(DeltaProof::Zero) => {
+                return Err(EncodingError::Evm(
+                    "zero delta proofs can't be verified on-chain".to_string(),
+                ))
+            }
+            Delta::Proof(proof) => proof.to_bytes().to_vec(),
+            Delta::Witness(_) => return Err(EncodingError::Evm("missing delta proof".to_string())),
+        };
+        if tx.aggregation_proof.is_some() {
+            return Err(EncodingError::Evm(
+                "aggregation proofs are not supported".to_string(),
+            ));
+        }
+        if !tx.private_actions.is_empty() {
+            return Err(EncodingError::Evm(
+                "function-private actions are not supported".to_string(),
+            ));
+        }
+        Ok(abi::Transaction {
+            actions: tx
+                .actions
+                .iter()
+                .map(abi::Action::try_from)
+                .collect::<Result<_, _>>()?,
+            deltaProof: delta_proof.into(),
+        })
+    }
+}
+
+impl TryFrom<&Action> for abi::Action {
+    type Error = EncodingError;
+
+    fn try_from(action: &Action) -> Result<Self, Self::Error> {
+        if !action.multi_compliance_units.is_empty() {
+            return Err(EncodingError::Evm(
+                "multi-resource compliance units are not supported".to_string(),
+            ));
+        }
+        Ok(abi::Action {
+            logicProofs: action
+                .logic_proofs
+                .iter()
+                .map(abi::LogicProof::try_from)
+                .collect::<Result<_, _>>()?,
+            complianceUnits: action
+                .compliance_units
+                .iter()
+                .map(|receipt| {
+                    Ok(abi::ComplianceUnit {
+                        proof: encode_seal(receipt)?.into(),
+                        instance: (&decode_journal::<ComplianceInstance>(receipt)?).into(),
+                    })
+                })
+                .collect::<Result<_, EncodingError>>()?,
+            resourceCalldataPairs: action
+                .resource_forwarder_calldata_pairs
+                .iter()
+                .map(|(resource, calldata)| abi::ResourceForwarderCalldataPair {
+                    carrier: resource.into(),
+                    call: calldata.into(),
+                })
+                .collect(),
+        })
+    }
+}
+
+impl TryFrom<&LogicProof> for abi::LogicProof {
+    type Error = EncodingError;
+
+    fn try_from(proof: &LogicProof) -> Result<Self, Self::Error> {
+        Ok(abi::LogicProof {
+            proof: encode_seal(&proof.receipt)?.into(),
+            instance: (&decode_journal::<LogicInstance>(&proof.receipt)?).into(),
+            logicRef: digest_to_b256(&proof.verifying_key),
+        })
+    }
+}
+
+impl From<&LogicInstance> for abi::LogicInstance {
+    fn from(instance: &LogicInstance) -> Self {
+        abi::LogicInstance {
+            tag: digest_to_b256(&instance.tag),
+            isConsumed: instance.is_consumed,
+            actionTreeRoot: digest_to_b256(&instance.root),
+            actionTreeDepth: instance.action_tree_depth,
+            ciphertext: instance.cipher.clone().into(),
+            appData: instance
+                .app_data
+                .iter()
+                .map(|blob| abi::ExpirableBlob {
+                    deletionCriterion: blob.deletion_criterion,
+                    blob: blob.blob.clone().into(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<abi::LogicInstance> for LogicInstance {
+    fn from(instance: abi::LogicInstance) -> Self {
+        LogicInstance {
+            tag: b256_to_digest(instance.tag),
+            is_consumed: instance.isConsumed,
+            root: b256_to_digest(instance.actionTreeRoot),
+            action_tree_depth: instance.actionTreeDepth,
+            cipher: instance.ciphertext.to_vec(),
+            app_data: instance
+                .appData
+                .into_iter()
+                .map(|blob| ExpirableBlob {
+                    blob: blob.blob.to_vec(),
+                    deletion_criterion: blob.deletionCriterion,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&ComplianceInstance> for abi::ComplianceInstance {
+    fn from(instance: &ComplianceInstance) -> Self {
+        abi::ComplianceInstance {
+            consumed: abi::ConsumedRefs {
+                nullifier: digest_to_b256(&instance.consumed_nullifier),
+                logicRef: digest_to_b256(&instance.consumed_logic_ref),
+                commitmentTreeRoot: digest_to_b256(&instance.consumed_commitment_tree_root),
+            },
+            created: abi::CreatedRefs {
+                commitment: digest_to_b256(&instance.created_commitment),
+                logicRef: digest_to_b256(&instance.created_logic_ref),
+            },
+            unitDeltaX: digest_to_b256(&instance.delta_x),
+            unitDeltaY: digest_to_b256(&instance.delta_y),
+        }
+    }
+}
+
+impl From<abi::ComplianceInstance> for ComplianceInstance {
+    fn from(instance: abi::ComplianceInstance) -> Self {
+        ComplianceInstance {
+            consumed_nullifier: b256_to_digest(instance.consumed.nullifier),
+            consumed_logic_ref: b256_to_digest(instance.consumed.logicRef),
+            consumed_commitment_tree_root: b256_to_digest(instance.consumed.commitmentTreeRoot),
+            created_commitment: b256_to_digest(instance.created.commitment),
+            created_logic_ref: b256_to_digest(instance.created.logicRef),
+            delta_x: b256_to_digest(instance.unitDeltaX),
+            delta_y: b256_to_digest(instance.unitDeltaY),
+        }
+    }
+}
+
+impl From<&Resource> for abi::Resource {
+    fn from(resource: &Resource) -> Self {
+        abi::Resource {
+            logicRef: digest_to_b256(&resource.logic_ref),
+            labelRef: digest_to_b256(&resource.label_ref),
+            valueRef: digest_to_b256(&resource.value_ref),
+            nullifierKeyCommitment: digest_to_b256(&resource.nk_commitment.inner()),
+            quantity: U256::from(resource.quantity),
+            nonce: U256::from_be_bytes(resource.nonce),
+            randSeed: U256::from_be_bytes(resource.rand_seed),
+            ephemeral: resource.is_ephemeral,
+        }
+    }
+}
+
+impl TryFrom<abi::Resource> for Resource {
+    type Error = EncodingError;
+
+    fn try_from(resource: abi::Resource) -> Result<Self, Self::Error> {
+        Ok(Resource {
+            logic_ref: b256_to_digest(resource.logicRef),
+            label_ref: b256_to_digest(resource.labelRef),
+            quantity: resource
+                .quantity
+                .try_into()
+                .map_err(|_| EncodingError::Abi("quantity exceeds u128".to_string()))?,
+            value_ref: b256_to_digest(resource.valueRef),
+            is_ephemeral: resource.ephemeral,
+            nonce: resource.nonce.to_be_bytes(),
+            nk_commitment: NullifierKeyCommitment::from_bytes(resource.nullifierKeyCommitment.0),
+            rand_seed: resource.randSeed.to_be_bytes(),
+        })
+    }
+}
+
+impl From<&ForwarderCalldata> for abi::ForwarderCalldata {
+    fn from(calldata: &ForwarderCalldata) -> Self {
+        abi::ForwarderCalldata {
+            untrustedForwarder: Address::from(calldata.untrusted_forwarder),
+            input: Bytes::from(calldata.input.clone()),
+            output: Bytes::from(calldata.output.clone()),
+        }
+    }
+}
+
+impl From<abi::ForwarderCalldata> for ForwarderCalldata {
+    fn from(calldata: abi::ForwarderCalldata) -> Self {
+        ForwarderCalldata {
+            untrusted_forwarder: calldata.untrustedForwarder.into_array(),
+            input: calldata.input.to_vec(),
+            output: calldata.output.to_vec(),
+        }
+    }
+}
+
+/// Encodes a receipt as a seal for the RISC Zero verifier router.
+pub fn encode_seal(receipt: &Receipt) -> Result<Vec<u8>, EncodingError> {
+    match &receipt.inner {
+        InnerReceipt::Groth16(inner) => {
+            let mut seal = inner.verifier_parameters.as_bytes()[..4].to_vec();
+            seal.extend_from_slice(&inner.seal);
+            Ok(seal)
+        }
+        InnerReceipt::Fake(inner) => {
+            let mut seal = FAKE_SELECTOR.to_vec();
+            seal.extend_from_slice(inner.claim.digest().as_bytes());
+            Ok(seal)
+        }
+        _ => Err(EncodingError::Evm(
+            "only Groth16 receipts can be verified on-chain".to_string(),
+        )),
+    }
+}
+
+fn decode_journal<T: serde::de::DeserializeOwned>(receipt: &Receipt) -> Result<T, EncodingError> {
+    receipt
+        .journal
+        .decode()
+        .map_err(|e| EncodingError::Invalid(format!("journal: {e}")))
+}
+
+fn digest_to_b256(digest: &Digest) -> B256 {
+    B256::from_slice(digest.as_bytes())
+}
+
+fn b256_to_digest(word: B256) -> Digest {
+    Digest::from_bytes(word.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fake_receipt;
+    use aarm_core::delta_proof::DeltaWitness;
+    use k256::Scalar;
+
+    fn test_transaction() -> Transaction {
+        let compliance = ComplianceInstance {
+            consumed_nullifier: Digest::from([1u32; 8]),
+            consumed_logic_ref: Digest::from([2u32; 8]),
+            created_commitment: Digest::from([3u32; 8]),
+            created_logic_ref: Digest::from([2u32; 8]),
+            ..Default::default()
+        };
+        let logic = LogicInstance {
+            tag: compliance.consumed_nullifier,
+            is_consumed: true,
+            cipher: vec![4; 40],
+            app_data: vec![ExpirableBlob {
+                blob: vec![5, 6],
+                deletion_criterion: 1,
+            }],
+            ..Default::default()
+        };
+        let resource = Resource {
+            logic_ref: compliance.consumed_logic_ref,
+            label_ref: Digest::from([11u32; 8]),
+            quantity: 7,
+            value_ref: Digest::default(),
+            is_ephemeral: true,
+            nonce: [12; 32],
+            nk_commitment: NullifierKeyCommitment::default(),
+            rand_seed: [13; 32],
+        };
+        let calldata = ForwarderCalldata {
+            untrusted_forwarder: [8u8; 20],
+            input: vec![9],
+            output: vec![],
+        };
+        let action = Action::new(
+            vec![fake_receipt(Digest::from([10u32; 8]), &compliance)],
+            vec![LogicProof {
+                receipt: fake_receipt(compliance.consumed_logic_ref, &logic),
+                verifying_key: compliance.consumed_logic_ref,
+            }],
+            vec![(resource, calldata)],
+        );
+        let witness = DeltaWitness::from_scalars(&[Scalar::ONE]);
+        Transaction::new(
+            vec![action],
+            Delta::Proof(DeltaProof::prove(b"evm", &witness)),
+        )
+    }
+
+    #[test]
+    fn test_abi_layout() {
+        assert_eq!(
+            abi::executeCall::SIGNATURE,
+            "execute((((bytes,(bytes32,bool,bytes32,uint32,bytes,(uint8,bytes)[]),bytes32)[],\
+             (bytes,((bytes32,bytes32,bytes32),(bytes32,bytes32),bytes32,bytes32))[],\
+             ((bytes32,bytes32,bytes32,bytes32,uint256,uint256,uint256,bool),\
+             (address,bytes,bytes))[])[],bytes))"
+        );
+        assert_eq!(
+            hex::encode(test_transaction().to_evm_calldata().unwrap()),
+            include_str!("../testdata/evm_calldata_v1.hex").trim()
+        );
+    }
+
+    #[test]
+    fn test_evm_calldata_round_trip() {
+        let tx = test_transaction();
+        let calldata = tx.to_evm_calldata().unwrap();
+        assert_eq!(calldata[..4], abi::executeCall::SELECTOR);
+        let decoded = decode_evm_calldata(&calldata).unwrap();
+        assert_eq!(
+            abi::executeCall {
+                transaction: decoded.clone()
+            }
+            .abi_encode(),
+            calldata
+        );
+
+        let action = &decoded.actions[0];
+        assert_eq!(decoded.deltaProof.len(), 65);
+        assert_eq!(action.complianceUnits[0].proof[..4], FAKE_SELECTOR);
+        let compliance = ComplianceInstance::from(action.complianceUnits[0].instance.clone());
+        assert_eq!(compliance.created_commitment, Digest::from([3u32; 8]));
+        let logic = LogicInstance::from(action.logicProofs[0].instance.clone());
+        assert_eq!(logic.app_data[0].blob, vec![5, 6]);
+        assert!(logic.is_consumed);
+        let pair = &action.resourceCalldataPairs[0];
+        let (resource, calldata) = &tx.actions[0].resource_forwarder_calldata_pairs[0];
+        assert_eq!(
+            Resource::try_from(pair.carrier.clone())
+                .unwrap()
+                .commitment(),
+            resource.commitment()
+        );
+        assert_eq!(
+            ForwarderCalldata::from(pair.call.clone()).untrusted_forwarder,
+            calldata.untrusted_forwarder
+        );
+
+        let encoded = tx.to_evm_calldata().unwrap();
+        assert!(decode_evm_calldata(&encoded[..encoded.len() - 32]).is_err());
+        let mut wrong_selector = encoded.clone();
+        wrong_selector[0] ^= 1;
+        assert!(decode_evm_calldata(&wrong_selector).is_err());
+
+        let mut zero = tx.clone();
+        zero.delta_proof = Delta::Proof(DeltaProof::Zero);
+        assert!(matches!(zero.to_evm_calldata(), Err(EncodingError::Evm(_))));
+
+        // The contract can't check an aggregation proof
+        let mut aggregated = tx.clone();
+        aggregated.aggregation_proof = Some(tx.actions[0].compliance_units[0].clone());
+        assert!(matches!(
+            aggregated.to_evm_calldata(),
+            Err(EncodingError::Evm(_))
+        ));
+    }
+
+    // The contract only gets the instances: rebuilding the journals from
+    // them, as the risc0 serde encoding of the instances, must give back the
+    // claims the seals were made for
+    #[test]
+    fn test_journals_from_instances() {
+        use risc0_zkvm::ReceiptClaim;
+
+        fn journal<T: serde::Serialize>(instance: &T) -> Vec<u8> {
+            risc0_zkvm::serde::to_vec(instance)
+                .unwrap()
+                .iter()
+                .flat_map(|word| word.to_le_bytes())
+                .collect()
+        }
+        let seal = |image_id: Digest, journal: Vec<u8>| {
+            let mut seal = FAKE_SELECTOR.to_vec();
+            seal.extend_from_slice(ReceiptClaim::ok(image_id, journal).digest().as_bytes());
+            seal
+        };
+
+        let tx = test_transaction();
+        let decoded = decode_evm_calldata(&tx.to_evm_calldata().unwrap()).unwrap();
+        let (action, unit) = (&tx.actions[0], &decoded.actions[0].complianceUnits[0]);
+        let compliance = journal(&ComplianceInstance::from(unit.instance.clone()));
+        assert_eq!(compliance, action.compliance_units[0].journal.bytes);
+        assert_eq!(unit.proof, seal(Digest::from([10u32; 8]), compliance));
+
+        let proof = &decoded.actions[0].logicProofs[0];
+        let logic = journal(&LogicInstance::from(proof.instance.clone()));
+        assert_eq!(logic, action.logic_proofs[0].receipt.journal.bytes);
+        assert_eq!(proof.proof, seal(b256_to_digest(proof.logicRef), logic));
+    }
+}
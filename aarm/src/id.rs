@@ -0,0 +1,368 @@
+//! Deterministic identifiers of actions and transactions.
+//!
+//! An ID is the SHA-256 of a canonical encoding of the public data, so it
+//! does not depend on the wire or JSON representation, nor on the receipts:
+//! re-proving an action in another proof mode, or aggregating a transaction,
+//! keeps its ID.
+//!
+//! Integers are big-endian, lists and byte strings are prefixed with their
+//! length as a `u32`, and digests are their 32 bytes. Computing the ID of a
+//! value with a list or byte string longer than `u32::MAX` fails with
+//! `VerificationError::IdLengthOverflow`.
+//!
+//! The preimages, where `u32` is a big-endian length, `bytes` a `u32`
+//! length followed by the bytes, and `[...]` a `u32` count followed by the
+//! entries:
+//!
+//! ```text
+//! action ID = sha256(
+//!     "AARM_ACTION_ID_V1"
+//!     [compliance unit: [nullifier: bytes32] [commitment: bytes32]]
+//!     action tree root: bytes32
+//!     [logic proof:
+//!         verifying key: bytes32
+//!         tag: bytes32
+//!         is_consumed: uint8
+//!         cipher: bytes
+//!         [app data: deletion criterion: uint8, blob: bytes]]
+//!     [forwarder call:
+//!         resource commitment: bytes32
+//!         forwarder: address (20 bytes)
+//!         input: bytes
+//!         output: bytes])
+//!
+//! transaction ID = sha256(
+//!     "AARM_TRANSACTION_ID_V1"
+//!     [action ID: bytes32]
+//!     delta proof: 65 bytes)
+//! ```
+//!
+//! Single compliance units are encoded as multi units with one resource per
+//! side, and the action tree root is that of the tags of all the units. The
+//! private action ID is the same as the action ID over
+//! `AARM_PRIVATE_ACTION_ID_V1`, with the logic commitment of each logic
+//! proof in place of its verifying key. The action IDs of a transaction are
+//! those of the public actions followed by those of the private ones.
+
+use crate::{
+    action::{Action, ForwarderCalldata},
+    error::VerificationError,
+    private_action::PrivateAction,
+    transaction::{Delta, Transaction},
+};
+use aarm_core::{
+    action_tree::MerkleTree,
+    compliance::MultiComplianceInstance,
+    hasher::{HashFunction, Sha256Hasher},
+    logic_instance::{ExpirableBlob, LogicInstance, PrivateLogicInstance},
+    resource::Resource,
+};
+use risc0_zkvm::{Digest, Receipt};
+use serde::de::DeserializeOwned;
+
+const ACTION_ID_DOMAIN: &[u8] = b"AARM_ACTION_ID_V1";
+const PRIVATE_ACTION_ID_DOMAIN: &[u8] = b"AARM_PRIVATE_ACTION_ID_V1";
+const TRANSACTION_ID_DOMAIN: &[u8] = b"AARM_TRANSACTION_ID_V1";
+
+impl Action {
+    pub fn id(&self) -> Result<Digest, VerificationError> {
+        let mut encoder = Encoder::new(ACTION_ID_DOMAIN);
+        encoder.compliance_instances(&self.get_multi_compliance_instances()?)?;
+
+        encoder.len(self.logic_proofs.len())?;
+        for proof in &self.logic_proofs {
+            let instance: LogicInstance = decode_journal(&proof.receipt)?;
+            encoder.digest(&proof.verifying_key);
+            encoder.logic_instance(
+                &instance.tag,
+                instance.is_consumed,
+                &instance.cipher,
+                &instance.app_data,
+            )?;
+        }
+
+        encoder.forwarder_calls(
+            self.resource_forwarder_calldata_pairs
+                .iter()
+                .map(|(resource, calldata)| (resource, calldata)),
+        )?;
+        Ok(encoder.finish())
+    }
+}
+
+impl PrivateAction {
+    pub fn id(&self) -> Result<Digest, VerificationError> {
+        let mut encoder = Encoder::new(PRIVATE_ACTION_ID_DOMAIN);
+        encoder.compliance_instances(&self.get_multi_compliance_instances()?)?;
+
+        encoder.len(self.logic_proofs.len())?;
+        for proof in &self.logic_proofs {
+            let instance: PrivateLogicInstance = decode_journal(&proof.receipt)?;
+            encoder.digest(&instance.logic_cm);
+            encoder.logic_instance(
+                &instance.tag,
+                instance.is_consumed,
+                &instance.cipher,
+                &instance.app_data,
+            )?;
+        }
+
+        encoder.forwarder_calls(
+            self.forwarder_calls
+                .iter()
+                .map(|call| (&call.resource, &call.calldata)),
+        )?;
+        Ok(encoder.finish())
+    }
+}
+
+impl Transaction {
+    /// Returns the ID of the transaction, which is only defined once the
+    /// delta proof is generated.
+    pub fn id(&self) -> Result<Digest, VerificationError> {
+        let proof = match &self.delta_proof {
+            Delta::Proof(proof) => proof,
+            Delta::Witness(_) => return Err(VerificationError::MissingDeltaProof),
+        };
+
+        let mut encoder = Encoder::new(TRANSACTION_ID_DOMAIN);
+        encoder.len(self.actions.len() + self.private_actions.len())?;
+        for action in &self.actions {
+            encoder.digest(&action.id()?);
+        }
+        for action in &self.private_actions {
+            encoder.digest(&action.id()?);
+        }
+        encoder.raw(&proof.to_bytes());
+        Ok(encoder.finish())
+    }
+}
+
+struct Encoder(Vec<u8>);
+
+impl Encoder {
+    fn new(domain: &[u8]) -> Self {
+        Encoder(domain.to_vec())
+    }
+
+    fn raw(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn byte(&mut self, byte: u8) {
+        self.0.push(byte);
+    }
+
+    fn len(&mut self, len: usize) -> Result<(), VerificationError> {
+        let len = u32::try_from(len).map_err(|_| VerificationError::IdLengthOverflow(len))?;
+        self.raw(&len.to_be_bytes());
+        Ok(())
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) -> Result<(), VerificationError> {
+        self.len(bytes.len())?;
+        self.raw(bytes);
+        Ok(())
+    }
+
+    fn digest(&mut self, digest: &Digest) {
+        self.raw(digest.as_bytes());
+    }
+
+    fn digests(&mut self, digests: &[Digest]) -> Result<(), VerificationError> {
+        self.len(digests.len())?;
+        for digest in digests {
+            self.digest(digest);
+        }
+        Ok(())
+    }
+
+    // The tags of the compliance units and the action tree root
+    fn compliance_instances(
+        &mut self,
+        instances: &[MultiComplianceInstance],
+    ) -> Result<(), VerificationError> {
+        self.len(instances.len())?;
+        for instance in instances {
+            self.digests(&instance.consumed_nullifiers)?;
+            self.digests(&instance.created_commitments)?;
+        }
+
+        let tags = instances
+            .iter()
+            .flat_map(|instance| instance.tags())
+            .collect::<Vec<_>>();
+        self.digest(&MerkleTree::new(tags)?.root());
+        Ok(())
+    }
+
+    fn logic_instance(
+        &mut self,
+        tag: &Digest,
+        is_consumed: bool,
+        cipher: &[u8],
+        app_data: &[ExpirableBlob],
+    ) -> Result<(), VerificationError> {
+        self.digest(tag);
+        self.byte(is_consumed as u8);
+        self.bytes(cipher)?;
+        self.len(app_data.len())?;
+        for blob in app_data {
+            self.byte(blob.deletion_criterion);
+            self.bytes(&blob.blob)?;
+        }
+        Ok(())
+    }
+
+    fn forwarder_calls<'a>(
+        &mut self,
+        calls: impl ExactSizeIterator<Item = (&'a Resource, &'a ForwarderCalldata)>,
+    ) -> Result<(), VerificationError> {
+        self.len(calls.len())?;
+        for (resource, calldata) in calls {
+            self.digest(&resource.commitment());
+            self.raw(&calldata.untrusted_forwarder);
+            self.bytes(&calldata.input)?;
+            self.bytes(&calldata.output)?;
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Digest {
+        Sha256Hasher::hash_bytes(&self.0)
+    }
+}
+
+fn decode_journal<T: DeserializeOwned>(receipt: &Receipt) -> Result<T, VerificationError> {
+    receipt
+        .journal
+        .decode()
+        .map_err(|e| VerificationError::JournalDecode(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        action::ForwarderCalldata, encoding::WireFormat, logic_proof::LogicProof,
+        utils::fake_receipt,
+    };
+    use aarm_core::{
+        action_tree::ACTION_TREE_DEPTH,
+        compliance::{ComplianceInstance, MultiComplianceInstance},
+        delta_proof::{DeltaProof, DeltaWitness},
+        logic_instance::ExpirableBlob,
+        resource::Resource,
+    };
+    use k256::Scalar;
+
+    fn test_compliance_instance() -> ComplianceInstance {
+        ComplianceInstance {
+            consumed_nullifier: Digest::from([1u32; 8]),
+            consumed_logic_ref: Digest::from([2u32; 8]),
+            consumed_commitment_tree_root: Digest::from([3u32; 8]),
+            created_commitment: Digest::from([4u32; 8]),
+            created_logic_ref: Digest::from([2u32; 8]),
+            delta_x: Digest::from([5u32; 8]),
+            delta_y: Digest::from([6u32; 8]),
+        }
+    }
+
+    fn test_action(app_data: Vec<u8>) -> Action {
+        let compliance = test_compliance_instance();
+        let root = MerkleTree::new(vec![
+            compliance.consumed_nullifier,
+            compliance.created_commitment,
+        ])
+        .unwrap()
+        .root();
+        let logic_proofs = [
+            (compliance.consumed_nullifier, true),
+            (compliance.created_commitment, false),
+        ]
+        .into_iter()
+        .map(|(tag, is_consumed)| {
+            let instance = LogicInstance {
+                tag,
+                is_consumed,
+                root,
+                action_tree_depth: ACTION_TREE_DEPTH as u32,
+                cipher: vec![7, 8],
+                app_data: vec![ExpirableBlob {
+                    blob: app_data.clone(),
+                    deletion_criterion: 1,
+                }],
+            };
+            LogicProof {
+                receipt: fake_receipt(compliance.consumed_logic_ref, &instance),
+                verifying_key: compliance.consumed_logic_ref,
+            }
+        })
+        .collect();
+        let calldata = ForwarderCalldata {
+            untrusted_forwarder: [9u8; 20],
+            input: vec![10],
+            output: vec![11, 12],
+        };
+        Action::new(
+            vec![fake_receipt(Digest::from([13u32; 8]), &compliance)],
+            logic_proofs,
+            vec![(Resource::default(), calldata)],
+        )
+    }
+
+    fn test_transaction(app_data: Vec<u8>) -> Transaction {
+        let witness = DeltaWitness::from_scalars(&[Scalar::ONE]);
+        let proof = DeltaProof::prove(b"id", &witness);
+        Transaction::new(vec![test_action(app_data)], Delta::Proof(proof))
+    }
+
+    #[test]
+    fn test_id_vectors() {
+        let tx = test_transaction(vec![14, 15]);
+        assert_eq!(
+            hex::encode(tx.actions[0].id().unwrap()),
+            "26c0af588671e47e9c1ea7e14e0c07b51018628375930e59053672dccb98fc0d"
+        );
+        assert_eq!(
+            hex::encode(tx.id().unwrap()),
+            "20e7130a1cfb7b33523471dad27643b0309f4f282acda41cfcc22f48ec6e4395"
+        );
+    }
+
+    #[test]
+    fn test_id_is_canonical() {
+        let tx = test_transaction(vec![14, 15]);
+        let id = tx.id().unwrap();
+
+        // Independent of the serialization format and of the receipts
+        let decoded = Transaction::from_wire_bytes(&tx.to_wire_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.id().unwrap(), id);
+        let decoded = Transaction::from_json(&tx.to_json().unwrap()).unwrap();
+        assert_eq!(decoded.id().unwrap(), id);
+
+        let mut multi = tx.clone();
+        let instance = MultiComplianceInstance::from(test_compliance_instance());
+        multi.actions[0] = multi.actions[0]
+            .clone()
+            .with_multi_compliance_units(vec![fake_receipt(Digest::from([16u32; 8]), &instance)]);
+        multi.actions[0].compliance_units.clear();
+        assert_eq!(multi.actions[0].id(), tx.actions[0].id());
+
+        // Bound to the app data and the delta proof
+        assert_ne!(test_transaction(vec![14]).id().unwrap(), id);
+        let mut unproven = tx.clone();
+        unproven.delta_proof = Delta::Witness(DeltaWitness::from_scalars(&[Scalar::ONE]));
+        assert!(matches!(
+            unproven.id(),
+            Err(VerificationError::MissingDeltaProof)
+        ));
+
+        // Lengths beyond the u32 prefix are an error rather than a panic
+        let len = u32::MAX as usize + 1;
+        assert_eq!(
+            Encoder::new(ACTION_ID_DOMAIN).len(len),
+            Err(VerificationError::IdLengthOverflow(len))
+        );
+    }
+}
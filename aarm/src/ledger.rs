@@ -0,0 +1,244 @@
+use crate::{error::LedgerError, transaction::Transaction};
+use aarm_core::{
+    commitment_tree::CommitmentTree,
+    constants::COMMITMENT_TREE_DEPTH,
+    merkle_path::MerklePath,
+    sparse_merkle_tree::{SparseMerkleProof, SparseMerkleTree},
+};
+use risc0_zkvm::Digest;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// An in-process ledger that applies verified transactions to the global
+/// commitment tree and nullifier set.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Ledger {
+    commitment_tree: CommitmentTree<COMMITMENT_TREE_DEPTH>,
+    // Indexed by nullifier so that logics can be given (non-)membership proofs
+    nullifiers: SparseMerkleTree,
+    // All the distinct roots the commitment tree has had, oldest first
+    root_history: Vec<Digest>,
+    known_roots: HashSet<Digest>,
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        let commitment_tree = CommitmentTree::new();
+        let root = commitment_tree.root();
+        Ledger {
+            commitment_tree,
+            nullifiers: SparseMerkleTree::new(),
+            root_history: vec![root],
+            known_roots: HashSet::from([root]),
+        }
+    }
+
+    /// Verifies the transaction against the current state and applies it.
+    /// The ledger is left untouched if the transaction is rejected.
+    pub fn apply(&mut self, tx: &Transaction) -> Result<(), LedgerError> {
+        tx.verify()?;
+
+        let mut nullifiers = Vec::new();
+        let mut commitments = Vec::new();
+        for instance in tx.get_multi_compliance_instances()? {
+            for root in &instance.consumed_commitment_tree_roots {
+                if !self.is_known_root(root) {
+                    return Err(LedgerError::UnknownRoot(*root));
+                }
+            }
+            for nullifier in &instance.consumed_nullifiers {
+                if self.is_nullifier_spent(nullifier) || nullifiers.contains(nullifier) {
+                    return Err(LedgerError::DoubleSpend(*nullifier));
+                }
+                nullifiers.push(*nullifier);
+            }
+            commitments.extend(instance.created_commitments);
+        }
+
+        if self.commitment_tree.size() + commitments.len() > 1 << COMMITMENT_TREE_DEPTH {
+            return Err(LedgerError::CommitmentTreeFull);
+        }
+
+        for nullifier in nullifiers {
+            self.nullifiers.insert(nullifier);
+        }
+        for cm in commitments {
+            self.commitment_tree
+                .append(cm)
+                .ok_or(LedgerError::CommitmentTreeFull)?;
+        }
+        // A transaction without created commitments leaves the root unchanged
+        let root = self.commitment_tree.root();
+        if self.known_roots.insert(root) {
+            self.root_history.push(root);
+        }
+        Ok(())
+    }
+
+    pub fn commitment_tree(&self) -> &CommitmentTree<COMMITMENT_TREE_DEPTH> {
+        &self.commitment_tree
+    }
+
+    pub fn commitment_tree_root(&self) -> Digest {
+        self.commitment_tree.root()
+    }
+
+    pub fn root_history(&self) -> &[Digest] {
+        &self.root_history
+    }
+
+    pub fn is_known_root(&self, root: &Digest) -> bool {
+        self.known_roots.contains(root)
+    }
+
+    pub fn is_nullifier_spent(&self, nullifier: &Digest) -> bool {
+        self.nullifiers.contains(nullifier)
+    }
+
+    pub fn nullifier_root(&self) -> Digest {
+        self.nullifiers.root()
+    }
+
+    /// Returns a proof that the nullifier is spent, or not spent, against
+    /// `nullifier_root`.
+    pub fn nullifier_proof(&self, nullifier: &Digest) -> SparseMerkleProof {
+        self.nullifiers.prove(nullifier)
+    }
+
+    pub fn contains_commitment(&self, cm: &Digest) -> bool {
+        self.commitment_tree.position(cm).is_some()
+    }
+
+    /// Returns the path of the commitment against the current root.
+    pub fn commitment_path(&self, cm: &Digest) -> Option<MerklePath<COMMITMENT_TREE_DEPTH>> {
+        let position = self.commitment_tree.position(cm)?;
+        self.commitment_tree.path(position)
+    }
+}
+
+impl Default for Ledger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[test]
+fn test_ledger() {
+    use crate::transaction::generate_test_transaction;
+
+    let mut ledger = Ledger::new();
+    let tx = generate_test_transaction(1);
+    let instances = tx.actions[0].get_compliance_instances().unwrap();
+
+    ledger.apply(&tx).unwrap();
+    for instance in &instances {
+        assert!(ledger.is_nullifier_spent(&instance.consumed_nullifier));
+        assert!(ledger
+            .nullifier_proof(&instance.consumed_nullifier)
+            .verify_membership(&ledger.nullifier_root(), &instance.consumed_nullifier));
+        let path = ledger
+            .commitment_path(&instance.created_commitment)
+            .unwrap();
+        assert_eq!(
+            path.root(instance.created_commitment),
+            ledger.commitment_tree_root()
+        );
+    }
+    assert_eq!(ledger.root_history().len(), 2);
+
+    // The same transaction can't be applied twice
+    assert_eq!(
+        ledger.apply(&tx),
+        Err(LedgerError::DoubleSpend(instances[0].consumed_nullifier))
+    );
+    assert_eq!(ledger.root_history().len(), 2);
+}
+
+#[test]
+fn test_ledger_rejection_leaves_state_unchanged() {
+    use crate::{
+        action::create_multiple_actions,
+        transaction::{generate_test_transaction, Delta},
+    };
+
+    let mut ledger = Ledger::new();
+    ledger.apply(&generate_test_transaction(1)).unwrap();
+    let state = bincode::serialize(&ledger).unwrap();
+
+    // The test actions are deterministic: the second one is fresh and the
+    // first one is already spent. The fresh one is checked first and must
+    // not be recorded when the spent one is rejected.
+    let (mut actions, delta_witness) = create_multiple_actions(2).unwrap();
+    actions.reverse();
+    let fresh = actions[0].get_compliance_instances().unwrap()[0].clone();
+    let spent = actions[1].get_compliance_instances().unwrap()[0].consumed_nullifier;
+    let mut tx = Transaction::new(actions, Delta::Witness(delta_witness));
+    tx.generate_delta_proof().unwrap();
+    assert_eq!(ledger.apply(&tx), Err(LedgerError::DoubleSpend(spent)));
+    assert!(!ledger.is_nullifier_spent(&fresh.consumed_nullifier));
+    assert!(!ledger.contains_commitment(&fresh.created_commitment));
+    assert_eq!(bincode::serialize(&ledger).unwrap(), state);
+}
+
+#[test]
+fn test_ledger_unknown_root() {
+    use crate::{
+        action::Action,
+        constants::{COMPLIANCE_GUEST_ELF, TEST_GUEST_ELF, TEST_GUEST_ID},
+        logic_proof::LogicProof,
+        transaction::Delta,
+        utils::{prove, ProofMode},
+    };
+    use aarm_core::{
+        action_tree::{MerkleTree, ACTION_TREE_DEPTH},
+        compliance::ComplianceWitness,
+        delta_proof::DeltaWitness,
+        nullifier_key::NullifierKey,
+        resource::Resource,
+        resource_logic::TrivialLogicWitness,
+    };
+
+    // The consumed resource is proven against a root the ledger never had
+    let (nf_key, nk_commitment) = NullifierKey::random_pair();
+    let consumed = Resource {
+        logic_ref: Digest::new(TEST_GUEST_ID),
+        nk_commitment,
+        ..Default::default()
+    };
+    let mut created = consumed;
+    created.nonce[0] = 1;
+    let mut compliance_witness =
+        ComplianceWitness::<COMMITMENT_TREE_DEPTH>::from_resources(consumed, nf_key, created);
+    let root = Digest::from([1u32; 8]);
+    compliance_witness.ephemeral_root = root;
+
+    let tags = vec![consumed.nullifier(&nf_key).unwrap(), created.commitment()];
+    let paths = MerkleTree::new(tags)
+        .unwrap()
+        .generate_paths::<ACTION_TREE_DEPTH>()
+        .unwrap();
+    let logic_proofs = [(consumed, true), (created, false)]
+        .into_iter()
+        .zip(paths)
+        .map(|((resource, is_consumed), path)| {
+            let witness = TrivialLogicWitness::new(resource, path, nf_key, is_consumed);
+            LogicProof {
+                receipt: prove(&witness, TEST_GUEST_ELF, ProofMode::Dev).unwrap(),
+                verifying_key: TEST_GUEST_ID.into(),
+            }
+        })
+        .collect();
+    let action = Action::new(
+        vec![prove(&compliance_witness, COMPLIANCE_GUEST_ELF, ProofMode::Dev).unwrap()],
+        logic_proofs,
+        vec![],
+    );
+    let delta_witness = DeltaWitness::from_scalars(&[compliance_witness.rcv]);
+    let mut tx = Transaction::new(vec![action], Delta::Witness(delta_witness));
+    tx.generate_delta_proof().unwrap();
+
+    let mut ledger = Ledger::new();
+    let state = bincode::serialize(&ledger).unwrap();
+    assert_eq!(ledger.apply(&tx), Err(LedgerError::UnknownRoot(root)));
+    assert_eq!(bincode::serialize(&ledger).unwrap(), state);
+}
@@ -1,5 +1,13 @@
 pub mod action;
+pub mod action_builder;
+pub mod balance;
 pub mod constants;
+pub mod encoding;
+pub mod error;
+pub mod evm;
+pub mod id;
+pub mod ledger;
 pub mod logic_proof;
+pub mod private_action;
 pub mod transaction;
 pub mod utils;
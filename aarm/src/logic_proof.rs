@@ -1,6 +1,7 @@
 use crate::{
     constants::{PADDING_GUEST_ELF, PADDING_GUEST_ID},
-    utils::{groth16_prove, verify as verify_proof},
+    error::ProvingError,
+    utils::{prove, verify as verify_proof, ProofMode},
 };
 use aarm_core::{
     action_tree::ACTION_TREE_DEPTH, merkle_path::MerklePath, nullifier_key::NullifierKey,
@@ -19,12 +20,12 @@ pub trait LogicProver: Default + Clone + Serialize + for<'de> Deserialize<'de> {
 
     fn witness(&self) -> &Self::Witness;
 
-    fn prove(&self) -> LogicProof {
-        let receipt = groth16_prove(self.witness(), Self::proving_key());
-        LogicProof {
+    fn prove(&self, mode: ProofMode) -> Result<LogicProof, ProvingError> {
+        let receipt = prove(self.witness(), Self::proving_key(), mode)?;
+        Ok(LogicProof {
             receipt,
             verifying_key: Self::verifying_key(),
-        }
+        })
     }
 }
 
@@ -109,6 +110,26 @@ impl Default for PaddingResourceLogic {
 #[test]
 fn test_trivial_logic_prover() {
     let trivial_logic = PaddingResourceLogic::default();
-    let proof = trivial_logic.prove();
+    let proof = trivial_logic.prove(ProofMode::Groth16).unwrap();
+    assert!(proof.verify());
+}
+
+#[test]
+fn test_trivial_logic_prover_succinct() {
+    let trivial_logic = PaddingResourceLogic::default();
+    let proof = trivial_logic.prove(ProofMode::Succinct).unwrap();
+    // The dev mode prover only produces fake receipts
+    if !risc0_zkvm::is_dev_mode() {
+        assert!(proof.receipt.inner.succinct().is_ok());
+    }
     assert!(proof.verify());
 }
+
+#[test]
+fn test_trivial_logic_prover_constraint_failure() {
+    let mut trivial_logic = PaddingResourceLogic::default();
+    // The padding resource must have zero quantity
+    trivial_logic.witness.resource.quantity = 1;
+    let err = trivial_logic.prove(ProofMode::Dev).unwrap_err();
+    assert!(matches!(err, ProvingError::Prover(_)));
+}
@@ -0,0 +1,326 @@
+use crate::{
+    action::{tag_positions, verify_forwarder_bindings, BindingInstance, ForwarderCalldata},
+    constants::{LOGIC_WRAPPER_GUEST_ELF, LOGIC_WRAPPER_GUEST_ID, PRIVATE_COMPLIANCE_GUEST_ID},
+    error::{ProvingError, VerificationError},
+    logic_proof::LogicProof,
+    utils::{prove_with_assumptions, verify as verify_proof, ProofMode},
+};
+use aarm_core::{
+    action_tree::MerkleTree,
+    compliance::{logic_commitment, MultiComplianceInstance, PrivateComplianceInstance},
+    constants::DEFAULT_BYTES,
+    logic_instance::{LogicWrapperWitness, PrivateLogicInstance},
+    resource::Resource,
+};
+use k256::ProjectivePoint;
+use risc0_zkvm::Receipt;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A logic proof wrapped in the logic wrapper circuit, which proves that a
+/// valid proof exists for the committed logic ref without revealing it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PrivateLogicProof {
+    pub receipt: Receipt,
+}
+
+impl PrivateLogicProof {
+    pub fn wrap(
+        proof: &LogicProof,
+        logic_cm_r: [u8; DEFAULT_BYTES],
+        mode: ProofMode,
+    ) -> Result<Self, ProvingError> {
+        let witness = LogicWrapperWitness {
+            logic_ref: proof.verifying_key,
+            logic_cm_r,
+            journal: proof.receipt.journal.bytes.clone(),
+        };
+        let receipt = prove_with_assumptions(
+            &witness,
+            LOGIC_WRAPPER_GUEST_ELF,
+            vec![proof.receipt.clone()],
+            mode,
+        )?;
+        Ok(PrivateLogicProof { receipt })
+    }
+
+    pub fn verify(&self) -> bool {
+        verify_proof(&self.receipt, LOGIC_WRAPPER_GUEST_ID)
+    }
+}
+
+/// The function-private counterpart of `Action`: compliance units publish
+/// logic commitments and logic proofs are wrapped, so the logic refs of the
+/// resources never appear in the action.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PrivateAction {
+    pub compliance_units: Vec<Receipt>,
+    pub logic_proofs: Vec<PrivateLogicProof>,
+    pub forwarder_calls: Vec<PrivateForwarderCall>,
+}
+
+/// A forwarder call of a private action. Its carrier resource is public, so
+/// the commitment to the carrier logic is opened with `logic_cm_r`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PrivateForwarderCall {
+    pub resource: Resource,
+    pub logic_cm_r: [u8; DEFAULT_BYTES],
+    pub calldata: ForwarderCalldata,
+}
+
+impl PrivateAction {
+    pub fn new(
+        compliance_units: Vec<Receipt>,
+        logic_proofs: Vec<PrivateLogicProof>,
+        forwarder_calls: Vec<PrivateForwarderCall>,
+    ) -> Self {
+        PrivateAction {
+            compliance_units,
+            logic_proofs,
+            forwarder_calls,
+        }
+    }
+
+    pub fn verify(&self) -> Result<(), VerificationError> {
+        for (index, receipt) in self.compliance_units.iter().enumerate() {
+            if !verify_proof(receipt, PRIVATE_COMPLIANCE_GUEST_ID) {
+                return Err(VerificationError::InvalidComplianceReceipt(index));
+            }
+        }
+
+        let compliance_intances = self.get_compliance_instances()?;
+
+        // Construct the action tree
+        let tags = compliance_intances
+            .iter()
+            .flat_map(|instance| vec![instance.consumed_nullifier, instance.created_commitment])
+            .collect::<Vec<_>>();
+        let logic_cms = compliance_intances
+            .iter()
+            .flat_map(|instance| vec![instance.consumed_logic_cm, instance.created_logic_cm])
+            .collect::<Vec<_>>();
+        let action_tree = MerkleTree::new(tags.clone())?;
+        let root = action_tree.root();
+
+        // Tags alternate between nullifiers and commitments
+        let positions = tag_positions(&tags, |position| position % 2 == 0)?;
+
+        let mut proven = vec![false; tags.len()];
+        for (index, proof) in self.logic_proofs.iter().enumerate() {
+            let instance: PrivateLogicInstance = decode_journal(&proof.receipt)?;
+
+            if root != instance.root {
+                return Err(VerificationError::RootMismatch(index));
+            }
+            if instance.action_tree_depth as usize != action_tree.depth() {
+                return Err(VerificationError::ActionTreeDepthMismatch(index));
+            }
+
+            if let Some(&position) = positions.get(&instance.tag) {
+                if instance.logic_cm != logic_cms[position] {
+                    return Err(VerificationError::LogicCommitmentMismatch(index));
+                }
+                if instance.is_consumed != (position % 2 == 0) {
+                    return Err(VerificationError::ConsumedFlagMismatch(index));
+                }
+                if std::mem::replace(&mut proven[position], true) {
+                    return Err(VerificationError::DuplicateLogicProof(index));
+                }
+            } else {
+                return Err(VerificationError::TagNotFound(index));
+            }
+
+            if !proof.verify() {
+                return Err(VerificationError::InvalidLogicReceipt(index));
+            }
+        }
+        if let Some(position) = proven.iter().position(|&proven| !proven) {
+            return Err(VerificationError::MissingLogicProof(tags[position]));
+        }
+
+        self.verify_forwarder_calls()
+    }
+
+    /// Checks the forwarder calls as `Action::verify_forwarder_calls` does,
+    /// the logic of a proof being matched through its logic commitment.
+    pub fn verify_forwarder_calls(&self) -> Result<(), VerificationError> {
+        if self.forwarder_calls.is_empty() {
+            return Ok(());
+        }
+
+        let tags = self
+            .get_multi_compliance_instances()?
+            .iter()
+            .flat_map(|instance| instance.tags().into_iter().zip(instance.is_consumed()))
+            .collect::<HashSet<_>>();
+        let instances = self
+            .logic_proofs
+            .iter()
+            .map(|proof| decode_journal::<PrivateLogicInstance>(&proof.receipt))
+            .collect::<Result<Vec<_>, _>>()?;
+        verify_forwarder_bindings(
+            self.forwarder_calls
+                .iter()
+                .map(|call| (&call.resource, &call.calldata)),
+            &tags,
+            &instances
+                .iter()
+                .map(|instance| BindingInstance {
+                    tag: instance.tag,
+                    is_consumed: instance.is_consumed,
+                    app_data: &instance.app_data,
+                })
+                .collect::<Vec<_>>(),
+            |call, proof| {
+                let call = &self.forwarder_calls[call];
+                instances[proof].logic_cm
+                    == logic_commitment(&call.resource.logic_ref, &call.logic_cm_r)
+            },
+        )
+    }
+
+    pub fn get_compliance_instances(
+        &self,
+    ) -> Result<Vec<PrivateComplianceInstance>, VerificationError> {
+        self.compliance_units.iter().map(decode_journal).collect()
+    }
+
+    /// Returns the compliance instances as multi units, with the logic
+    /// commitments in place of the logic refs. This is enough for the checks
+    /// on tags, roots and deltas shared with public actions.
+    pub fn get_multi_compliance_instances(
+        &self,
+    ) -> Result<Vec<MultiComplianceInstance>, VerificationError> {
+        Ok(self
+            .get_compliance_instances()?
+            .into_iter()
+            .map(|instance| MultiComplianceInstance {
+                consumed_nullifiers: vec![instance.consumed_nullifier],
+                consumed_logic_refs: vec![instance.consumed_logic_cm],
+                consumed_commitment_tree_roots: vec![instance.consumed_commitment_tree_root],
+                created_commitments: vec![instance.created_commitment],
+                created_logic_refs: vec![instance.created_logic_cm],
+                delta_x: instance.delta_x,
+                delta_y: instance.delta_y,
+            })
+            .collect())
+    }
+
+    pub fn get_delta(&self) -> Result<Vec<ProjectivePoint>, VerificationError> {
+        Ok(self
+            .get_compliance_instances()?
+            .iter()
+            .map(|instance| instance.delta_projective())
+            .collect())
+    }
+
+    pub fn get_delta_msg(&self) -> Result<Vec<u8>, VerificationError> {
+        let mut msg = Vec::new();
+        for instance in self.get_compliance_instances()? {
+            msg.extend_from_slice(&instance.delta_msg());
+        }
+        Ok(msg)
+    }
+}
+
+fn decode_journal<T: DeserializeOwned>(receipt: &Receipt) -> Result<T, VerificationError> {
+    receipt
+        .journal
+        .decode()
+        .map_err(|e| VerificationError::JournalDecode(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::{PRIVATE_COMPLIANCE_GUEST_ELF, TEST_GUEST_ELF, TEST_GUEST_ID},
+        transaction::{Delta, Transaction},
+        utils::prove,
+    };
+    use aarm_core::{
+        action_tree::ACTION_TREE_DEPTH,
+        compliance::{ComplianceWitness, PrivateComplianceWitness},
+        constants::COMMITMENT_TREE_DEPTH,
+        delta_proof::DeltaWitness,
+        nullifier_key::NullifierKey,
+        resource_logic::TrivialLogicWitness,
+    };
+    use risc0_zkvm::Digest;
+
+    #[test]
+    fn test_private_action() {
+        let nf_key = NullifierKey::new(Digest::default());
+        let resource = |nonce: u8| {
+            let mut resource = Resource {
+                logic_ref: Digest::new(TEST_GUEST_ID),
+                nk_commitment: nf_key.commit(),
+                is_ephemeral: true,
+                ..Default::default()
+            };
+            resource.nonce[0] = nonce;
+            resource
+        };
+        let (consumed, created) = (resource(1), resource(2));
+
+        let compliance_witness = PrivateComplianceWitness::new(ComplianceWitness::<
+            COMMITMENT_TREE_DEPTH,
+        >::from_resources(
+            consumed, nf_key, created
+        ));
+        let compliance_receipt = prove(
+            &compliance_witness,
+            PRIVATE_COMPLIANCE_GUEST_ELF,
+            ProofMode::Dev,
+        )
+        .unwrap();
+
+        let instance = compliance_witness.constrain();
+        let tags = vec![instance.consumed_nullifier, instance.created_commitment];
+        let action_tree = MerkleTree::new(tags.clone()).unwrap();
+        let logic_proofs = [
+            (consumed, true, compliance_witness.consumed_logic_cm_r),
+            (created, false, compliance_witness.created_logic_cm_r),
+        ]
+        .into_iter()
+        .zip(tags)
+        .map(|((resource, is_consumed, logic_cm_r), tag)| {
+            let path = action_tree.generate_path::<ACTION_TREE_DEPTH>(tag).unwrap();
+            let witness = TrivialLogicWitness::new(resource, path, nf_key, is_consumed);
+            let proof = LogicProof {
+                receipt: prove(&witness, TEST_GUEST_ELF, ProofMode::Dev).unwrap(),
+                verifying_key: TEST_GUEST_ID.into(),
+            };
+            PrivateLogicProof::wrap(&proof, logic_cm_r, ProofMode::Dev).unwrap()
+        })
+        .collect();
+
+        let mut action = PrivateAction::new(vec![compliance_receipt], logic_proofs, vec![]);
+        assert!(action.verify().is_ok());
+
+        let mut tx = Transaction::new(
+            vec![],
+            Delta::Witness(DeltaWitness::from_scalars(&[compliance_witness
+                .compliance_witness
+                .rcv])),
+        )
+        .with_private_actions(vec![action.clone()]);
+        tx.generate_delta_proof().unwrap();
+        assert!(tx.verify().is_ok());
+
+        // The trivial logic binds no forwarder call
+        action.forwarder_calls.push(PrivateForwarderCall {
+            resource: created,
+            logic_cm_r: compliance_witness.created_logic_cm_r,
+            calldata: ForwarderCalldata {
+                untrusted_forwarder: [1u8; 20],
+                input: vec![],
+                output: vec![],
+            },
+        });
+        assert_eq!(
+            action.verify(),
+            Err(VerificationError::ForwarderCallNotBound(0))
+        );
+    }
+}
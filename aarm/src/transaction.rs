@@ -1,12 +1,33 @@
 use crate::action::create_multiple_actions;
 use crate::action::Action;
+use crate::error::{CompositionError, VerificationError};
+use crate::private_action::PrivateAction;
+use crate::{
+    constants::{
+        AGGREGATION_GUEST_ELF, AGGREGATION_GUEST_ID, COMPLIANCE_GUEST_ID, MULTI_COMPLIANCE_GUEST_ID,
+    },
+    error::ProvingError,
+    utils::{prove_with_assumptions, verify as verify_proof, ProofMode},
+};
+use aarm_core::aggregation::{AggregationInstance, AggregationWitness};
+use aarm_core::compliance::MultiComplianceInstance;
 use aarm_core::delta_proof::{DeltaInstance, DeltaProof, DeltaWitness};
+use risc0_zkvm::Digest;
+use risc0_zkvm::Receipt;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Transaction {
     pub actions: Vec<Action>,
+    // Function-private actions, balanced together with `actions`
+    #[serde(default)]
+    pub private_actions: Vec<PrivateAction>,
     pub delta_proof: Delta,
+    // A single receipt covering all the compliance and logic proofs, see
+    // `Transaction::aggregate`
+    #[serde(default)]
+    pub aggregation_proof: Option<Receipt>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -15,79 +36,296 @@ pub enum Delta {
     Proof(DeltaProof),
 }
 
+const DELTA_MSG_DOMAIN: &[u8] = b"AARM_DELTA_MSG";
+
+/// The format of the message signed by the delta proof.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DeltaMessageFormat {
+    /// The nullifiers and commitments of the compliance units, as signed
+    /// before the message was versioned. Only accepted by `verify` with the
+    /// `legacy-delta-msg` feature.
+    Legacy,
+    /// The domain tag `AARM_DELTA_MSG`, the version as a big-endian `u16`,
+    /// the number of actions as a big-endian `u32` and the action IDs, those
+    /// of the public actions first, which cover the tags, action tree roots,
+    /// app data and forwarder calls. On the EVM, this is
+    /// `abi.encodePacked(bytes14("AARM_DELTA_MSG"), uint16(1), uint32(n), ids)`
+    /// with `ids` the `bytes32[]` of the IDs recomputed as described in the
+    /// `id` module, and the delta proof signs its Keccak-256.
+    #[default]
+    V1,
+}
+
 impl Transaction {
     pub fn new(actions: Vec<Action>, delta: Delta) -> Self {
         Transaction {
             actions,
+            private_actions: Vec::new(),
             delta_proof: delta,
+            aggregation_proof: None,
         }
     }
 
-    pub fn generate_delta_proof(&mut self) {
+    pub fn with_private_actions(mut self, private_actions: Vec<PrivateAction>) -> Self {
+        self.private_actions = private_actions;
+        self
+    }
+
+    pub fn generate_delta_proof(&mut self) -> Result<(), VerificationError> {
         match self.delta_proof {
             Delta::Witness(ref witness) => {
-                let msg = self.get_delta_msg();
+                let msg = self.get_delta_msg()?;
                 let proof = DeltaProof::prove(&msg, witness);
                 self.delta_proof = Delta::Proof(proof);
             }
             Delta::Proof(_) => {}
         }
+        Ok(())
     }
 
-    pub fn verify(&self) -> bool {
+    /// Verifies the actions and the delta proof.
+    ///
+    /// A `DeltaProof::Zero` signs no message, so the set of actions of such
+    /// a transaction is only bound by their deltas summing to the identity,
+    /// and the content of each action by its action tree, to which all its
+    /// logic proofs commit. As the `rcv`s are random, actions moved between
+    /// transactions only balance if a whole balanced set of actions is
+    /// moved, which is then a valid transaction on its own.
+    pub fn verify(&self) -> Result<(), VerificationError> {
         match &self.delta_proof {
             Delta::Proof(ref proof) => {
-                let msg = self.get_delta_msg();
-                let instance = self.get_delta_instance();
-                if DeltaProof::verify(&msg, proof, instance).is_err() {
-                    return false;
-                }
+                // Verify the actions first so that the delta is only derived
+                // from verified compliance instances
                 for action in &self.actions {
-                    if !action.verify() {
-                        return false;
-                    }
+                    action.verify()?;
                 }
-                true
+                for action in &self.private_actions {
+                    action.verify()?;
+                }
+                self.verify_unique_tags()?;
+                let instance = self.get_delta_instance()?;
+                self.verify_delta_msg(proof, instance)
             }
-            Delta::Witness(_) => false,
+            Delta::Witness(_) => Err(VerificationError::MissingDeltaProof),
         }
     }
 
-    pub fn get_delta_instance(&self) -> DeltaInstance {
-        let deltas = self
+    /// Replaces the compliance and logic proofs of all the actions by a
+    /// single aggregated receipt, checked by `verify_aggregation`. Private
+    /// actions keep their own proofs.
+    pub fn aggregate(&mut self, mode: ProofMode) -> Result<(), ProvingError> {
+        let mut assumptions = Vec::new();
+        for action in &self.actions {
+            assumptions.extend(action.compliance_units.iter().cloned());
+            assumptions.extend(action.multi_compliance_units.iter().cloned());
+            assumptions.extend(
+                action
+                    .logic_proofs
+                    .iter()
+                    .map(|proof| proof.receipt.clone()),
+            );
+        }
+
+        let witness = AggregationWitness {
+            compliance_id: COMPLIANCE_GUEST_ID.into(),
+            multi_compliance_id: MULTI_COMPLIANCE_GUEST_ID.into(),
+            actions: self.actions.iter().map(Action::get_journals).collect(),
+        };
+        let receipt = prove_with_assumptions(&witness, AGGREGATION_GUEST_ELF, assumptions, mode)?;
+        self.aggregation_proof = Some(receipt);
+        Ok(())
+    }
+
+    /// Verifies the transaction with its aggregation proof instead of the
+    /// individual proofs of the actions. The proof commits to the journals of
+    /// each action, which must be those of `actions`: the delta message and
+    /// the transaction ID are derived from them.
+    pub fn verify_aggregation(&self) -> Result<(), VerificationError> {
+        let receipt = self
+            .aggregation_proof
+            .as_ref()
+            .ok_or(VerificationError::MissingAggregationProof)?;
+        if !verify_proof(receipt, AGGREGATION_GUEST_ID) {
+            return Err(VerificationError::InvalidAggregationReceipt);
+        }
+        let instance: AggregationInstance = receipt
+            .journal
+            .decode()
+            .map_err(|e| VerificationError::JournalDecode(e.to_string()))?;
+        if instance.compliance_id != Digest::from(COMPLIANCE_GUEST_ID)
+            || instance.multi_compliance_id != Digest::from(MULTI_COMPLIANCE_GUEST_ID)
+        {
+            return Err(VerificationError::AggregationComplianceMismatch);
+        }
+
+        if instance.action_digests.len() != self.actions.len() {
+            return Err(VerificationError::AggregationActionMismatch(
+                instance.action_digests.len().min(self.actions.len()),
+            ));
+        }
+        for (index, (action, digest)) in self
             .actions
             .iter()
-            .flat_map(|action| action.get_delta())
+            .zip(&instance.action_digests)
+            .enumerate()
+        {
+            if action.get_journals().digest() != *digest {
+                return Err(VerificationError::AggregationActionMismatch(index));
+            }
+            // The forwarder calls are not covered by the aggregated proofs
+            action.verify_forwarder_calls()?;
+        }
+        for action in &self.private_actions {
+            action.verify()?;
+        }
+
+        match &self.delta_proof {
+            Delta::Proof(ref proof) => {
+                self.verify_unique_tags()?;
+                let instance = self.get_delta_instance()?;
+                self.verify_delta_msg(proof, instance)
+            }
+            Delta::Witness(_) => Err(VerificationError::MissingDeltaProof),
+        }
+    }
+
+    pub fn get_delta_instance(&self) -> Result<DeltaInstance, VerificationError> {
+        let deltas = self
+            .get_multi_compliance_instances()?
+            .iter()
+            .map(|instance| instance.delta_projective())
             .collect::<Vec<_>>();
-        DeltaInstance::from_deltas(&deltas).unwrap()
+        Ok(DeltaInstance::from_deltas(&deltas))
     }
 
-    pub fn get_delta_msg(&self) -> Vec<u8> {
-        let mut msg = Vec::new();
+    /// Returns the compliance instances of the public actions, then of the
+    /// private actions, see `PrivateAction::get_multi_compliance_instances`.
+    pub fn get_multi_compliance_instances(
+        &self,
+    ) -> Result<Vec<MultiComplianceInstance>, VerificationError> {
+        let mut instances = Vec::new();
         for action in &self.actions {
-            msg.extend(action.get_delta_msg());
+            instances.extend(action.get_multi_compliance_instances()?);
+        }
+        for action in &self.private_actions {
+            instances.extend(action.get_multi_compliance_instances()?);
         }
-        msg
+        Ok(instances)
     }
 
-    pub fn compose(tx1: Transaction, tx2: Transaction) -> Transaction {
-        let mut actions = tx1.actions;
-        actions.extend(tx2.actions);
+    pub fn get_delta_msg(&self) -> Result<Vec<u8>, VerificationError> {
+        self.get_delta_msg_with_format(DeltaMessageFormat::default())
+    }
+
+    pub fn get_delta_msg_with_format(
+        &self,
+        format: DeltaMessageFormat,
+    ) -> Result<Vec<u8>, VerificationError> {
+        let mut msg = Vec::new();
+        match format {
+            DeltaMessageFormat::Legacy => {
+                for action in &self.actions {
+                    msg.extend(action.get_delta_msg()?);
+                }
+                for action in &self.private_actions {
+                    msg.extend(action.get_delta_msg()?);
+                }
+            }
+            DeltaMessageFormat::V1 => {
+                let n_actions = self.actions.len() + self.private_actions.len();
+                msg.extend_from_slice(DELTA_MSG_DOMAIN);
+                msg.extend_from_slice(&1u16.to_be_bytes());
+                msg.extend_from_slice(&(n_actions as u32).to_be_bytes());
+                for action in &self.actions {
+                    msg.extend_from_slice(action.id()?.as_bytes());
+                }
+                for action in &self.private_actions {
+                    msg.extend_from_slice(action.id()?.as_bytes());
+                }
+            }
+        }
+        Ok(msg)
+    }
+
+    fn verify_delta_msg(
+        &self,
+        proof: &DeltaProof,
+        instance: DeltaInstance,
+    ) -> Result<(), VerificationError> {
+        let result = verify_delta_proof(&self.get_delta_msg()?, proof, instance.clone());
+        #[cfg(feature = "legacy-delta-msg")]
+        if result.is_err() {
+            let legacy_msg = self.get_delta_msg_with_format(DeltaMessageFormat::Legacy)?;
+            return verify_delta_proof(&legacy_msg, proof, instance);
+        }
+        result
+    }
+
+    /// Checks that the nullifiers and the commitments are unique across all
+    /// the actions.
+    pub fn verify_unique_tags(&self) -> Result<(), VerificationError> {
+        check_unique_tags(self.get_multi_compliance_instances()?)
+    }
+
+    /// Merges the actions and the delta witnesses of two unproven
+    /// transactions, which must not consume or create the same resources.
+    pub fn compose(tx1: Transaction, tx2: Transaction) -> Result<Transaction, CompositionError> {
         let delta = match (&tx1.delta_proof, &tx2.delta_proof) {
             (Delta::Witness(witness1), Delta::Witness(witness2)) => {
                 Delta::Witness(witness1.compose(witness2))
             }
-            _ => panic!("Cannot compose transactions with different delta types"),
+            _ => return Err(CompositionError::DeltaMismatch),
         };
-        Transaction::new(actions, delta)
+        let mut actions = tx1.actions;
+        actions.extend(tx2.actions);
+        let mut private_actions = tx1.private_actions;
+        private_actions.extend(tx2.private_actions);
+        let tx = Transaction::new(actions, delta).with_private_actions(private_actions);
+        tx.verify_unique_tags().map_err(|e| match e {
+            VerificationError::DuplicateNullifier(nf) => CompositionError::DuplicateNullifier(nf),
+            VerificationError::DuplicateCommitment(cm) => CompositionError::DuplicateCommitment(cm),
+            e => CompositionError::Verification(e),
+        })?;
+        Ok(tx)
+    }
+}
+
+fn check_unique_tags(
+    instances: impl IntoIterator<Item = MultiComplianceInstance>,
+) -> Result<(), VerificationError> {
+    let mut nullifiers = HashSet::new();
+    let mut commitments = HashSet::new();
+    for instance in instances {
+        for nf in instance.consumed_nullifiers {
+            if !nullifiers.insert(nf) {
+                return Err(VerificationError::DuplicateNullifier(nf));
+            }
+        }
+        for cm in instance.created_commitments {
+            if !commitments.insert(cm) {
+                return Err(VerificationError::DuplicateCommitment(cm));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn verify_delta_proof(
+    msg: &[u8],
+    proof: &DeltaProof,
+    instance: DeltaInstance,
+) -> Result<(), VerificationError> {
+    match DeltaProof::verify(msg, proof, instance) {
+        Ok(true) => Ok(()),
+        _ => Err(VerificationError::DeltaProofFailed),
     }
 }
 
 pub fn generate_test_transaction(n_actions: usize) -> Transaction {
-    let (actions, delta_witness) = create_multiple_actions(n_actions);
+    let (actions, delta_witness) = create_multiple_actions(n_actions).unwrap();
     let mut tx = Transaction::new(actions, Delta::Witness(delta_witness));
-    tx.generate_delta_proof();
-    assert!(tx.verify());
+    tx.generate_delta_proof().unwrap();
+    assert!(tx.verify().is_ok());
     tx
 }
 
@@ -99,4 +337,193 @@ mod tests {
     fn test_transaction() {
         let _ = generate_test_transaction(1);
     }
+
+    #[test]
+    fn test_delta_message_formats() {
+        use crate::{action::ForwarderCalldata, utils::fake_receipt};
+        use aarm_core::{compliance::ComplianceInstance, resource::Resource};
+        use k256::{elliptic_curve::sec1::ToEncodedPoint, ProjectivePoint, Scalar};
+        use risc0_zkvm::Digest;
+
+        // A balanced unit whose delta is only its randomness
+        let rcv = Scalar::from(7u64);
+        let delta = (ProjectivePoint::GENERATOR * rcv)
+            .to_affine()
+            .to_encoded_point(false);
+        let instance = ComplianceInstance {
+            consumed_nullifier: Digest::from([1u32; 8]),
+            created_commitment: Digest::from([2u32; 8]),
+            delta_x: Digest::try_from(&delta.x().unwrap()[..]).unwrap(),
+            delta_y: Digest::try_from(&delta.y().unwrap()[..]).unwrap(),
+            ..Default::default()
+        };
+        let action = Action::new(vec![fake_receipt([0u32; 8], &instance)], vec![], vec![]);
+        let witness = DeltaWitness::from_scalars(&[rcv]);
+        let mut tx = Transaction::new(vec![action], Delta::Witness(witness.clone()));
+
+        // abi.encodePacked(bytes14("AARM_DELTA_MSG"), uint16(1), uint32(1), ids)
+        let msg = tx.get_delta_msg().unwrap();
+        let id = tx.actions[0].id().unwrap();
+        assert_eq!(
+            msg,
+            [b"AARM_DELTA_MSG\x00\x01\x00\x00\x00\x01", id.as_bytes()].concat()
+        );
+        let proof = DeltaProof::prove(&msg, &witness);
+        let instance = tx.get_delta_instance().unwrap();
+        assert!(tx.verify_delta_msg(&proof, instance.clone()).is_ok());
+
+        let legacy_msg = tx
+            .get_delta_msg_with_format(DeltaMessageFormat::Legacy)
+            .unwrap();
+        let legacy_proof = DeltaProof::prove(&legacy_msg, &witness);
+        assert_eq!(
+            tx.verify_delta_msg(&legacy_proof, instance.clone()).is_ok(),
+            cfg!(feature = "legacy-delta-msg")
+        );
+
+        // Forwarder calls are bound by the message
+        tx.actions[0].resource_forwarder_calldata_pairs.push((
+            Resource::default(),
+            ForwarderCalldata {
+                untrusted_forwarder: [3u8; 20],
+                input: vec![],
+                output: vec![],
+            },
+        ));
+        assert_ne!(tx.get_delta_msg().unwrap(), msg);
+        assert_eq!(
+            tx.verify_delta_msg(&proof, instance),
+            Err(VerificationError::DeltaProofFailed)
+        );
+    }
+
+    #[test]
+    fn test_aggregation() {
+        use crate::action::tests::create_a_multi_compliance_action;
+
+        let (action1, witness1) = create_a_multi_compliance_action(1);
+        let (action2, witness2) = create_a_multi_compliance_action(2);
+        let mut tx = Transaction::new(
+            vec![action1, action2],
+            Delta::Witness(witness1.compose(&witness2)),
+        );
+        tx.generate_delta_proof().unwrap();
+        tx.aggregate(ProofMode::Dev).unwrap();
+        assert!(tx.verify_aggregation().is_ok());
+
+        // A relayer swaps an action for another one with the same balance
+        let mut tampered = tx.clone();
+        tampered.actions[1] = create_a_multi_compliance_action(3).0;
+        assert_eq!(
+            tampered.verify_aggregation(),
+            Err(VerificationError::AggregationActionMismatch(1))
+        );
+
+        // Or drops one
+        let mut tampered = tx.clone();
+        tampered.actions.pop();
+        assert_eq!(
+            tampered.verify_aggregation(),
+            Err(VerificationError::AggregationActionMismatch(1))
+        );
+    }
+
+    #[test]
+    fn test_compose_rejects_overlaps() {
+        use crate::utils::fake_receipt;
+        use aarm_core::compliance::ComplianceInstance;
+        use k256::Scalar;
+        use risc0_zkvm::Digest;
+
+        let unproven_tx = |nullifier: u32, commitment: u32| {
+            let instance = ComplianceInstance {
+                consumed_nullifier: Digest::from([nullifier; 8]),
+                created_commitment: Digest::from([commitment; 8]),
+                ..Default::default()
+            };
+            let action = Action::new(vec![fake_receipt([0u32; 8], &instance)], vec![], vec![]);
+            let witness = DeltaWitness::from_scalars(&[Scalar::ONE]);
+            Transaction::new(vec![action], Delta::Witness(witness))
+        };
+
+        let tx = Transaction::compose(unproven_tx(1, 2), unproven_tx(3, 4)).unwrap();
+        assert_eq!(tx.actions.len(), 2);
+        assert_eq!(
+            Transaction::compose(unproven_tx(1, 2), unproven_tx(1, 4)).unwrap_err(),
+            CompositionError::DuplicateNullifier(Digest::from([1u32; 8]))
+        );
+        assert_eq!(
+            Transaction::compose(unproven_tx(1, 2), unproven_tx(3, 2)).unwrap_err(),
+            CompositionError::DuplicateCommitment(Digest::from([2u32; 8]))
+        );
+
+        let mut proven = unproven_tx(3, 4);
+        let witness = DeltaWitness::from_scalars(&[Scalar::ONE]);
+        proven.delta_proof = Delta::Proof(DeltaProof::prove(b"", &witness));
+        assert_eq!(
+            Transaction::compose(unproven_tx(1, 2), proven).unwrap_err(),
+            CompositionError::DeltaMismatch
+        );
+    }
+
+    #[test]
+    fn test_compose_cancelling_rcvs() {
+        use crate::utils::fake_receipt;
+        use aarm_core::compliance::ComplianceInstance;
+        use k256::{elliptic_curve::sec1::ToEncodedPoint, ProjectivePoint, Scalar};
+        use risc0_zkvm::Digest;
+
+        // Balanced partial transactions with opposite randomness
+        let unproven_tx = |tag: u32, rcv: Scalar| {
+            let delta = (ProjectivePoint::GENERATOR * rcv)
+                .to_affine()
+                .to_encoded_point(false);
+            let instance = ComplianceInstance {
+                consumed_nullifier: Digest::from([tag; 8]),
+                created_commitment: Digest::from([tag + 1; 8]),
+                delta_x: Digest::try_from(&delta.x().unwrap()[..]).unwrap(),
+                delta_y: Digest::try_from(&delta.y().unwrap()[..]).unwrap(),
+                ..Default::default()
+            };
+            let action = Action::new(vec![fake_receipt([0u32; 8], &instance)], vec![], vec![]);
+            Transaction::new(
+                vec![action],
+                Delta::Witness(DeltaWitness::from_scalars(&[rcv])),
+            )
+        };
+
+        let rcv = Scalar::from(7u64);
+        let mut tx = Transaction::compose(unproven_tx(1, rcv), unproven_tx(3, -rcv)).unwrap();
+        let instance = tx.get_delta_instance().unwrap();
+        assert!(instance.is_identity());
+        tx.generate_delta_proof().unwrap();
+        let Delta::Proof(proof) = &tx.delta_proof else {
+            panic!("missing delta proof");
+        };
+        assert_eq!(*proof, DeltaProof::Zero);
+        assert!(tx.verify_delta_msg(proof, instance).is_ok());
+
+        // The zero proof does not verify a delta with randomness left
+        let partial = unproven_tx(1, rcv);
+        assert_eq!(
+            partial.verify_delta_msg(proof, partial.get_delta_instance().unwrap()),
+            Err(VerificationError::DeltaProofFailed)
+        );
+
+        // Swapping actions between two zero-delta transactions unbalances
+        // both of them
+        let other_rcv = Scalar::from(11u64);
+        let mut other =
+            Transaction::compose(unproven_tx(5, other_rcv), unproven_tx(7, -other_rcv)).unwrap();
+        other.generate_delta_proof().unwrap();
+        std::mem::swap(&mut tx.actions[1], &mut other.actions[1]);
+        for swapped in [&tx, &other] {
+            let instance = swapped.get_delta_instance().unwrap();
+            assert!(!instance.is_identity());
+            assert_eq!(
+                swapped.verify_delta_msg(&DeltaProof::Zero, instance),
+                Err(VerificationError::DeltaProofFailed)
+            );
+        }
+    }
 }
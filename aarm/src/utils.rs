@@ -1,28 +1,95 @@
-use risc0_zkvm::{default_prover, sha::Digest, ExecutorEnv, ProverOpts, Receipt, VerifierContext};
-use serde::Serialize;
+use crate::error::ProvingError;
+use risc0_zkvm::{
+    default_executor, default_prover, sha::Digest, ExecutorEnv, FakeReceipt, InnerReceipt,
+    ProverOpts, Receipt, VerifierContext,
+};
+use serde::{Deserialize, Serialize};
 
-// TODO: handle errors properly
-pub fn groth16_prove<T: Serialize>(witness: &T, proving_key: &[u8]) -> Receipt {
-    let env = ExecutorEnv::builder()
+/// The kind of receipt produced by the prover.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProofMode {
+    /// One STARK per segment, no recursion.
+    Composite,
+    /// A single STARK compressed by recursion.
+    Succinct,
+    /// A Groth16 SNARK wrapping the succinct receipt, required on-chain.
+    #[default]
+    Groth16,
+    /// A fake receipt that only verifies when RISC0_DEV_MODE is set. Not for production.
+    Dev,
+}
+
+pub fn prove<T: Serialize>(
+    witness: &T,
+    proving_key: &[u8],
+    mode: ProofMode,
+) -> Result<Receipt, ProvingError> {
+    prove_with_assumptions(witness, proving_key, vec![], mode)
+}
+
+/// Proves a guest that recursively verifies the given receipts with
+/// `env::verify`.
+pub fn prove_with_assumptions<T: Serialize>(
+    witness: &T,
+    proving_key: &[u8],
+    assumptions: Vec<Receipt>,
+    mode: ProofMode,
+) -> Result<Receipt, ProvingError> {
+    let mut builder = ExecutorEnv::builder();
+    for assumption in assumptions {
+        builder.add_assumption(assumption);
+    }
+    let env = builder
         .write(witness)
-        .unwrap()
+        .map_err(|e| ProvingError::WitnessSerialization(e.to_string()))?
         .build()
-        .unwrap();
+        .map_err(ProvingError::Prover)?;
+
+    let opts = match mode {
+        ProofMode::Composite => ProverOpts::composite(),
+        ProofMode::Succinct => ProverOpts::succinct(),
+        ProofMode::Groth16 => ProverOpts::groth16(),
+        ProofMode::Dev => {
+            // Only execute the guest and attach the claim to a fake receipt
+            let session = default_executor()
+                .execute(env, proving_key)
+                .map_err(ProvingError::Prover)?;
+            let claim = session
+                .receipt_claim
+                .ok_or_else(|| ProvingError::Prover(anyhow::anyhow!("missing receipt claim")))?;
+            return Ok(Receipt::new(
+                InnerReceipt::Fake(FakeReceipt::new(claim)),
+                session.journal.bytes,
+            ));
+        }
+    };
 
     default_prover()
-        .prove_with_ctx(
-            env,
-            &VerifierContext::default(),
-            proving_key,
-            &ProverOpts::groth16(),
-        )
-        .unwrap()
-        .receipt
+        .prove_with_ctx(env, &VerifierContext::default(), proving_key, &opts)
+        .map(|info| info.receipt)
+        .map_err(ProvingError::Prover)
 }
 
-// TODO: add a stark prove API
+pub fn groth16_prove<T: Serialize>(
+    witness: &T,
+    proving_key: &[u8],
+) -> Result<Receipt, ProvingError> {
+    prove(witness, proving_key, ProofMode::Groth16)
+}
 
 // Receipt contains the proof and the public inputs
 pub fn verify(receipt: &Receipt, verifying_key: impl Into<Digest>) -> bool {
     receipt.verify(verifying_key).is_ok()
 }
+
+// A fake receipt of `journal`, for tests that don't run the prover
+#[cfg(test)]
+pub(crate) fn fake_receipt<T: Serialize>(image_id: impl Into<Digest>, journal: &T) -> Receipt {
+    let journal = risc0_zkvm::serde::to_vec(journal)
+        .unwrap()
+        .into_iter()
+        .flat_map(u32::to_le_bytes)
+        .collect::<Vec<_>>();
+    let claim = risc0_zkvm::ReceiptClaim::ok(image_id.into(), journal.clone());
+    Receipt::new(InnerReceipt::Fake(FakeReceipt::new(claim)), journal)
+}
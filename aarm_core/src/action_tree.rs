@@ -1,37 +1,103 @@
-use crate::merkle_path::{Hashable, MerklePath};
+use crate::{
+    hasher::{HashFunction, Sha256Hasher},
+    merkle_path::{MerkleMultiProof, MerklePath},
+};
 use risc0_zkvm::sha::Digest;
+use std::marker::PhantomData;
 
-pub const ACTION_TREE_MAX_NUM: usize = 1 << ACTION_TREE_DEPTH;
+// The default and minimum depth of an action tree. The logic circuits
+// shipped with this crate (the trivial, padding and kudo logics) take
+// existence paths of this depth, so actions proved with them, e.g. through
+// `ActionBuilder`, hold at most `1 << ACTION_TREE_DEPTH` = 16 tags. Deeper
+// trees need logic circuits built for their depth, e.g. with
+// `TrivialLogicWitness<D>`, which commit it as `action_tree_depth`.
 pub const ACTION_TREE_DEPTH: usize = 4;
+// The depth up to which untrusted actions are accepted. It bounds the size
+// of the trees a verifier rebuilds, to 2^16 tags or 2^15 compliance units,
+// and is not a limit of the logic circuits, which fix their own depth.
+pub const MAX_ACTION_TREE_DEPTH: usize = 16;
+// The maximum number of tags in an action
+pub const ACTION_TREE_MAX_NUM: usize = 1 << MAX_ACTION_TREE_DEPTH;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActionTreeError {
+    /// The tree would be deeper than `MAX_ACTION_TREE_DEPTH`.
+    TooDeep(usize),
+}
+
+impl std::fmt::Display for ActionTreeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActionTreeError::TooDeep(depth) => write!(
+                f,
+                "action tree depth {depth} exceeds the maximum of {MAX_ACTION_TREE_DEPTH}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ActionTreeError {}
+
+/// The action tree grows with the number of tags: its depth is the smallest
+/// depth, but at least `ACTION_TREE_DEPTH`, that fits all the leaves.
+///
+/// Nodes are hashed with `H`, like `CommitmentTree`; the paths of a tree
+/// hashed with another function than SHA-256 are checked with
+/// `MerklePath::root_with_hasher`.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct MerkleTree {
+pub struct MerkleTree<H: HashFunction = Sha256Hasher> {
     leaves: Vec<Digest>,
+    min_depth: usize,
+    hasher: PhantomData<H>,
 }
 
 impl MerkleTree {
-    pub fn new(leaves: Vec<Digest>) -> Self {
-        assert!(
-            leaves.len() <= ACTION_TREE_MAX_NUM,
-            "The number of leaves exceeds the ACTION_TREE_MAX_NUM"
-        );
-        MerkleTree { leaves }
+    pub fn new(leaves: Vec<Digest>) -> Result<Self, ActionTreeError> {
+        Self::with_depth(leaves, ACTION_TREE_DEPTH)
+    }
+
+    /// Creates a tree of at least `min_depth`, e.g. to match the depth the
+    /// logic circuits were built for.
+    pub fn with_depth(leaves: Vec<Digest>, min_depth: usize) -> Result<Self, ActionTreeError> {
+        Self::with_hasher(leaves, min_depth)
     }
+}
 
-    pub fn insert(&mut self, value: Digest) {
-        self.leaves.push(value)
+impl<H: HashFunction> MerkleTree<H> {
+    /// Creates a tree of at least `min_depth` hashed with `H`.
+    pub fn with_hasher(leaves: Vec<Digest>, min_depth: usize) -> Result<Self, ActionTreeError> {
+        let tree = MerkleTree {
+            leaves,
+            min_depth,
+            hasher: PhantomData,
+        };
+        match tree.depth() {
+            depth if depth > MAX_ACTION_TREE_DEPTH => Err(ActionTreeError::TooDeep(depth)),
+            _ => Ok(tree),
+        }
     }
 
-    pub fn root(&self) -> Digest {
-        let mut cur_layer = self.leaves.clone();
-        cur_layer.resize(ACTION_TREE_MAX_NUM, Digest::blank());
-        while cur_layer.len() > 1 {
-            cur_layer = cur_layer
-                .chunks(2)
-                .map(|pair| Digest::combine(&pair[0], &pair[1]))
-                .collect();
+    /// Appends a leaf and returns its index. The tree is left unchanged if
+    /// it is full.
+    pub fn insert(&mut self, value: Digest) -> Result<usize, ActionTreeError> {
+        if self.leaves.len() == ACTION_TREE_MAX_NUM {
+            return Err(ActionTreeError::TooDeep(MAX_ACTION_TREE_DEPTH + 1));
         }
-        cur_layer[0]
+        self.leaves.push(value);
+        Ok(self.leaves.len() - 1)
+    }
+
+    pub fn leaves(&self) -> &[Digest] {
+        &self.leaves
+    }
+
+    pub fn depth(&self) -> usize {
+        let min_depth_for_leaves = self.leaves.len().next_power_of_two().trailing_zeros() as usize;
+        self.min_depth.max(min_depth_for_leaves)
+    }
+
+    pub fn root(&self) -> Digest {
+        self.layers().pop().unwrap()[0]
     }
 
     // Generate the merkle path for the current leave
@@ -43,51 +109,220 @@ impl MerkleTree {
     ///
     /// # Returns
     ///
-    /// Returns an `Option` containing a `MerklePath` of depth `ACTION_TREE_DEPTH` if the leaf exists in the tree.
+    /// Returns an `Option` containing a `MerklePath` of depth `D` for the first occurrence of the leaf.
     /// The `MerklePath` is a vector of tuples, where each tuple contains:
     /// - A `Digest` representing the sibling node's hash.
     /// - A `bool` indicating whether the sibling is on the left (`true`) or right (`false`).
     ///
-    /// Returns `None` if the leaf is not found in the tree.
-    pub fn generate_path(&self, cur_leave: Digest) -> Option<MerklePath<ACTION_TREE_DEPTH>> {
-        let mut cur_layer = self.leaves.clone();
-        cur_layer.resize(ACTION_TREE_MAX_NUM, Digest::blank());
-        if let Some(position) = cur_layer.iter().position(|&v| v == cur_leave) {
-            let mut merkle_path = Vec::new();
-            fn build_merkle_path_inner(
-                cur_layer: Vec<Digest>,
-                position: usize,
-                path: &mut Vec<(Digest, bool)>,
-            ) {
-                if cur_layer.len() > 1 {
-                    let sibling = {
-                        let is_sibling_left = position % 2 != 0;
-                        let sibling_value = if is_sibling_left {
-                            cur_layer[position - 1]
-                        } else {
-                            cur_layer[position + 1]
-                        };
-                        (sibling_value, is_sibling_left)
-                    };
-                    path.push(sibling);
-
-                    let prev_layer = cur_layer
-                        .chunks(2)
-                        .map(|pair| Digest::combine(&pair[0], &pair[1]))
-                        .collect();
-
-                    build_merkle_path_inner(prev_layer, position / 2, path);
+    /// Returns `None` if the leaf is not found in the tree or if `D` is not the depth of the tree.
+    /// Use `path_at` when the tree may contain duplicate leaves.
+    pub fn generate_path<const D: usize>(&self, cur_leave: Digest) -> Option<MerklePath<D>> {
+        let index = self.leaves.iter().position(|&v| v == cur_leave)?;
+        self.path_at(index)
+    }
+
+    /// Generates the Merkle path of the leaf at `index`.
+    ///
+    /// Returns `None` if `index` is out of bounds or if `D` is not the depth of the tree.
+    pub fn path_at<const D: usize>(&self, index: usize) -> Option<MerklePath<D>> {
+        if index >= self.leaves.len() || D != self.depth() {
+            return None;
+        }
+        Some(Self::path_from_layers(&self.layers(), index))
+    }
+
+    /// Generates the Merkle paths of all the leaves, in leaf order, hashing
+    /// the tree only once.
+    ///
+    /// Returns `None` if `D` is not the depth of the tree.
+    pub fn generate_paths<const D: usize>(&self) -> Option<Vec<MerklePath<D>>> {
+        if D != self.depth() {
+            return None;
+        }
+        let layers = self.layers();
+        Some(
+            (0..self.leaves.len())
+                .map(|index| Self::path_from_layers(&layers, index))
+                .collect(),
+        )
+    }
+
+    /// Generates a multiproof of the leaves at `indices`.
+    ///
+    /// Returns `None` if an index is out of bounds or repeated, or if `D` is
+    /// not the depth of the tree.
+    pub fn generate_multiproof<const D: usize>(
+        &self,
+        indices: &[usize],
+    ) -> Option<MerkleMultiProof<D, H>> {
+        if D != self.depth() {
+            return None;
+        }
+        let mut positions = indices.to_vec();
+        positions.sort_unstable();
+        positions.dedup();
+        if positions.len() != indices.len() || positions.iter().any(|&i| i >= self.leaves.len()) {
+            return None;
+        }
+
+        let layers = self.layers();
+        let mut siblings = Vec::new();
+        for layer in layers.iter().take(D) {
+            let mut parents = Vec::with_capacity(positions.len());
+            let mut i = 0;
+            while i < positions.len() {
+                let position = positions[i];
+                if position % 2 == 0 && positions.get(i + 1) == Some(&(position + 1)) {
+                    // Both children are known
+                    i += 1;
+                } else {
+                    siblings.push(layer[position ^ 1]);
                 }
+                parents.push(position / 2);
+                i += 1;
             }
-            build_merkle_path_inner(cur_layer, position, &mut merkle_path);
-            Some(MerklePath::<ACTION_TREE_DEPTH>::from_path(
-                match merkle_path.try_into() {
-                    Ok(path) => path,
-                    Err(_) => return None, // Return None if the conversion fails
-                },
-            ))
-        } else {
-            None
+            positions = parents;
+        }
+        Some(MerkleMultiProof::from_parts(indices.to_vec(), siblings))
+    }
+
+    // All the layers of the tree, from the padded leaves to the root
+    fn layers(&self) -> Vec<Vec<Digest>> {
+        let mut cur_layer = self.leaves.clone();
+        cur_layer.resize(1 << self.depth(), H::blank());
+        let mut layers = vec![cur_layer];
+        while layers.last().unwrap().len() > 1 {
+            let next_layer = layers
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| H::combine(&pair[0], &pair[1]))
+                .collect();
+            layers.push(next_layer);
         }
+        layers
+    }
+
+    fn path_from_layers<const D: usize>(layers: &[Vec<Digest>], index: usize) -> MerklePath<D> {
+        let auth_path = std::array::from_fn(|level| {
+            let position = index >> level;
+            let is_sibling_left = position % 2 != 0;
+            (layers[level][position ^ 1], is_sibling_left)
+        });
+        MerklePath::from_path(auth_path)
+    }
+}
+
+#[test]
+fn test_keccak_action_tree() {
+    use crate::hasher::Keccak256Hasher;
+
+    let leaves: Vec<Digest> = (0..5u32).map(|i| Digest::from([i; 8])).collect();
+    let sha_tree = MerkleTree::new(leaves.clone()).unwrap();
+    let keccak_tree =
+        MerkleTree::<Keccak256Hasher>::with_hasher(leaves.clone(), ACTION_TREE_DEPTH).unwrap();
+    assert_ne!(keccak_tree.root(), sha_tree.root());
+
+    // Same padding and layout as the Keccak commitment tree
+    let commitment_tree = crate::commitment_tree::CommitmentTree::<
+        ACTION_TREE_DEPTH,
+        Keccak256Hasher,
+    >::from_leaves(&leaves)
+    .unwrap();
+    assert_eq!(keccak_tree.root(), commitment_tree.root());
+
+    let paths = keccak_tree.generate_paths::<ACTION_TREE_DEPTH>().unwrap();
+    for (leaf, path) in leaves.iter().zip(&paths) {
+        assert_eq!(
+            path.root_with_hasher::<Keccak256Hasher>(*leaf),
+            keccak_tree.root()
+        );
     }
+    let proof = keccak_tree
+        .generate_multiproof::<ACTION_TREE_DEPTH>(&[4, 1])
+        .unwrap();
+    assert_eq!(
+        proof.root(&[leaves[4], leaves[1]]),
+        Some(keccak_tree.root())
+    );
+}
+
+#[test]
+fn test_action_tree_depth() {
+    let leaves: Vec<Digest> = (0..20u32).map(|i| Digest::from([i; 8])).collect();
+
+    let tree = MerkleTree::new(leaves[..16].to_vec()).unwrap();
+    assert_eq!(tree.depth(), ACTION_TREE_DEPTH);
+
+    let mut tree = MerkleTree::new(leaves.clone()).unwrap();
+    assert_eq!(tree.depth(), 5);
+    let path = tree.generate_path::<5>(leaves[17]).unwrap();
+    assert_eq!(path.root(leaves[17]), tree.root());
+    assert!(tree
+        .generate_path::<ACTION_TREE_DEPTH>(leaves[17])
+        .is_none());
+
+    // A larger tree of the same leaves has a different root
+    let deeper_tree = MerkleTree::with_depth(leaves.clone(), 6).unwrap();
+    assert_ne!(deeper_tree.root(), tree.root());
+
+    tree.insert(Sha256Hasher::blank()).unwrap();
+    assert_eq!(tree.depth(), 5);
+
+    // Trees are bounded by MAX_ACTION_TREE_DEPTH
+    assert_eq!(
+        MerkleTree::with_depth(vec![], MAX_ACTION_TREE_DEPTH + 1),
+        Err(ActionTreeError::TooDeep(MAX_ACTION_TREE_DEPTH + 1))
+    );
+    let mut full_tree = MerkleTree::new(vec![Sha256Hasher::blank(); ACTION_TREE_MAX_NUM]).unwrap();
+    assert_eq!(full_tree.depth(), MAX_ACTION_TREE_DEPTH);
+    assert!(full_tree.insert(Sha256Hasher::blank()).is_err());
+    assert_eq!(full_tree.leaves().len(), ACTION_TREE_MAX_NUM);
+    assert!(MerkleTree::new(vec![Sha256Hasher::blank(); ACTION_TREE_MAX_NUM + 1]).is_err());
+}
+
+#[test]
+fn test_action_tree_duplicate_leaves() {
+    let mut tree = MerkleTree::new(vec![]).unwrap();
+    let leaf = Digest::from([1u32; 8]);
+    assert_eq!(tree.insert(leaf), Ok(0));
+    assert_eq!(tree.insert(Sha256Hasher::blank()), Ok(1));
+    assert_eq!(tree.insert(leaf), Ok(2));
+
+    let paths = tree.generate_paths::<ACTION_TREE_DEPTH>().unwrap();
+    assert_eq!(paths.len(), 3);
+    for (index, path) in paths.iter().enumerate() {
+        assert_eq!(*path, tree.path_at(index).unwrap());
+        assert_eq!(path.root(tree.leaves()[index]), tree.root());
+    }
+    // The duplicate leaf has its own path
+    assert_ne!(paths[0], paths[2]);
+    assert_eq!(tree.generate_path(leaf), Some(paths[0]));
+    assert!(tree.path_at::<ACTION_TREE_DEPTH>(3).is_none());
+}
+
+#[test]
+fn test_action_tree_multiproof() {
+    let leaves: Vec<Digest> = (0..11u32).map(|i| Digest::from([i; 8])).collect();
+    let tree = MerkleTree::new(leaves.clone()).unwrap();
+
+    let indices = [7, 0, 1, 10];
+    let proof = tree
+        .generate_multiproof::<ACTION_TREE_DEPTH>(&indices)
+        .unwrap();
+    let proven_leaves: Vec<Digest> = indices.iter().map(|&i| leaves[i]).collect();
+    assert_eq!(proof.root(&proven_leaves), Some(tree.root()));
+
+    // A multiproof of a single leaf is a plain path
+    let single = tree.generate_multiproof::<ACTION_TREE_DEPTH>(&[7]).unwrap();
+    assert_eq!(single.root(&[leaves[7]]), Some(tree.root()));
+
+    // Wrong leaves or leaf order
+    let mut swapped = proven_leaves.clone();
+    swapped.swap(0, 1);
+    assert_ne!(proof.root(&swapped), Some(tree.root()));
+    assert_eq!(proof.root(&proven_leaves[..3]), None);
+    assert!(tree
+        .generate_multiproof::<ACTION_TREE_DEPTH>(&[1, 1])
+        .is_none());
 }
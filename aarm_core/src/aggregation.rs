@@ -0,0 +1,133 @@
+use crate::{
+    action_tree::MerkleTree,
+    compliance::{ComplianceInstance, MultiComplianceInstance},
+    logic_instance::LogicInstance,
+};
+use risc0_zkvm::sha::{Digest, Impl, Sha256};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// The journals of the proofs of an action. The proofs themselves are added
+/// as assumptions and verified with `env::verify` in the aggregation guest.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ActionJournals {
+    pub compliance_journals: Vec<Vec<u8>>,
+    pub multi_compliance_journals: Vec<Vec<u8>>,
+    // The verifying key and the journal of each logic proof
+    pub logic_journals: Vec<(Digest, Vec<u8>)>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AggregationWitness {
+    pub compliance_id: Digest,
+    pub multi_compliance_id: Digest,
+    pub actions: Vec<ActionJournals>,
+}
+
+/// The public inputs of an aggregated transaction: the digests of the
+/// journals of each action, which the verifier recomputes from the actions
+/// it is given, so that the proofs can't be swapped for other ones.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AggregationInstance {
+    pub compliance_id: Digest,
+    pub multi_compliance_id: Digest,
+    pub action_digests: Vec<Digest>,
+}
+
+impl ActionJournals {
+    /// The SHA-256 of the risc0 serde encoding of the journals, the words
+    /// being taken as little-endian bytes.
+    pub fn digest(&self) -> Digest {
+        let words = risc0_zkvm::serde::to_vec(self).unwrap();
+        let bytes = words
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<_>>();
+        *Impl::hash_bytes(&bytes)
+    }
+
+    // The same checks as `Action::verify_logic_instances`
+    fn constrain(&self) {
+        let mut instances = self
+            .compliance_journals
+            .iter()
+            .map(|journal| MultiComplianceInstance::from(decode::<ComplianceInstance>(journal)))
+            .collect::<Vec<_>>();
+        instances.extend(
+            self.multi_compliance_journals
+                .iter()
+                .map(|journal| decode::<MultiComplianceInstance>(journal)),
+        );
+
+        // Construct the action tree
+        let tags = instances
+            .iter()
+            .flat_map(|instance| instance.tags())
+            .collect::<Vec<_>>();
+        let logics = instances
+            .iter()
+            .flat_map(|instance| instance.logic_refs())
+            .collect::<Vec<_>>();
+        let is_consumed = instances
+            .iter()
+            .flat_map(|instance| instance.is_consumed())
+            .collect::<Vec<_>>();
+        let action_tree = MerkleTree::new(tags.clone()).expect("Action tree too deep");
+        let root = action_tree.root();
+
+        let mut nullifiers = HashSet::new();
+        for (tag, _) in tags.iter().zip(&is_consumed).filter(|(_, &c)| c) {
+            assert!(nullifiers.insert(*tag), "Duplicate nullifier");
+        }
+
+        // Every tag must have exactly one logic proof
+        let mut proven = vec![false; tags.len()];
+        for (verifying_key, journal) in &self.logic_journals {
+            let instance = decode::<LogicInstance>(journal);
+            assert_eq!(instance.root, root, "Action tree root mismatch");
+            assert_eq!(
+                instance.action_tree_depth as usize,
+                action_tree.depth(),
+                "Action tree depth mismatch"
+            );
+            let position = tags
+                .iter()
+                .position(|&tag| tag == instance.tag)
+                .expect("Tag not found in the action tree");
+            assert_eq!(*verifying_key, logics[position], "Logic ref mismatch");
+            assert_eq!(
+                instance.is_consumed, is_consumed[position],
+                "Consumed flag mismatch"
+            );
+            assert!(!proven[position], "Duplicate logic proof");
+            proven[position] = true;
+        }
+        assert!(proven.iter().all(|&proven| proven), "Missing logic proof");
+    }
+}
+
+impl AggregationWitness {
+    // The same checks as `Action::verify`, except for the proof verification
+    // which is done by the guest and the forwarder calls which are not part
+    // of the journals
+    pub fn constrain(&self) -> AggregationInstance {
+        let action_digests = self
+            .actions
+            .iter()
+            .map(|action| {
+                action.constrain();
+                action.digest()
+            })
+            .collect();
+
+        AggregationInstance {
+            compliance_id: self.compliance_id,
+            multi_compliance_id: self.multi_compliance_id,
+            action_digests,
+        }
+    }
+}
+
+fn decode<T: DeserializeOwned>(journal: &[u8]) -> T {
+    risc0_zkvm::serde::from_slice(journal).unwrap()
+}
@@ -0,0 +1,251 @@
+use crate::{
+    hasher::{HashFunction, Sha256Hasher},
+    merkle_path::MerklePath,
+};
+use risc0_zkvm::sha::Digest;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, io, marker::PhantomData, path::Path};
+
+/// An append-only Merkle tree of resource commitments.
+///
+/// Every node of a completed subtree is stored, so authentication paths of
+/// any commitment are read off without rehashing and memory grows linearly
+/// with the number of commitments. The right-most incomplete node at each
+/// level is recomputed from empty subtree roots when needed.
+///
+/// Nodes are hashed with `H`, e.g. `Keccak256Hasher` to match a tree
+/// maintained on an EVM chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitmentTree<const TREE_DEPTH: usize, H: HashFunction = Sha256Hasher> {
+    // The completed nodes at each level, `nodes[0]` being the leaves
+    nodes: Vec<Vec<Digest>>,
+    // The position of the first occurrence of each commitment
+    positions: HashMap<Digest, usize>,
+    hasher: PhantomData<H>,
+}
+
+impl<const TREE_DEPTH: usize, H: HashFunction> CommitmentTree<TREE_DEPTH, H> {
+    pub fn new() -> Self {
+        CommitmentTree {
+            nodes: vec![Vec::new(); TREE_DEPTH + 1],
+            positions: HashMap::new(),
+            hasher: PhantomData,
+        }
+    }
+
+    pub fn from_leaves(leaves: &[Digest]) -> Option<Self> {
+        let mut tree = Self::new();
+        for leaf in leaves {
+            tree.append(*leaf)?;
+        }
+        Some(tree)
+    }
+
+    /// The number of commitments in the tree.
+    pub fn size(&self) -> usize {
+        self.nodes[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes[0].is_empty()
+    }
+
+    pub fn leaves(&self) -> &[Digest] {
+        &self.nodes[0]
+    }
+
+    /// Appends a commitment and returns its position, or `None` if the tree
+    /// is full.
+    pub fn append(&mut self, leaf: Digest) -> Option<usize> {
+        let position = self.size();
+        if position >= 1 << TREE_DEPTH {
+            return None;
+        }
+        self.nodes[0].push(leaf);
+        self.positions.entry(leaf).or_insert(position);
+
+        // Complete the parent nodes while the new node is a right child
+        let mut index = position;
+        for level in 0..TREE_DEPTH {
+            if index % 2 == 0 {
+                break;
+            }
+            let parent = H::combine(&self.nodes[level][index - 1], &self.nodes[level][index]);
+            self.nodes[level + 1].push(parent);
+            index /= 2;
+        }
+        Some(position)
+    }
+
+    pub fn root(&self) -> Digest {
+        match self.nodes[TREE_DEPTH].first() {
+            // The tree is full
+            Some(root) => *root,
+            None => self.frontier()[TREE_DEPTH],
+        }
+    }
+
+    /// Returns the position of the first occurrence of `leaf`.
+    pub fn position(&self, leaf: &Digest) -> Option<usize> {
+        self.positions.get(leaf).copied()
+    }
+
+    /// Generates the authentication path of the commitment at `position`.
+    pub fn path(&self, position: usize) -> Option<MerklePath<TREE_DEPTH>> {
+        if position >= self.size() {
+            return None;
+        }
+        let empty_roots = Self::empty_roots();
+        let frontier = self.frontier();
+        let auth_path = std::array::from_fn(|level| {
+            let index = position >> level;
+            let sibling_index = index ^ 1;
+            let completed = &self.nodes[level];
+            let sibling = match sibling_index.cmp(&completed.len()) {
+                std::cmp::Ordering::Less => completed[sibling_index],
+                std::cmp::Ordering::Equal => frontier[level],
+                std::cmp::Ordering::Greater => empty_roots[level],
+            };
+            (sibling, index % 2 != 0)
+        });
+        Some(MerklePath::from_path(auth_path))
+    }
+
+    /// Serializes the leaves of the tree; the inner nodes are recomputed on
+    /// restore.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(&self.nodes[0]).unwrap()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let leaves: Vec<Digest> = bincode::deserialize(bytes).ok()?;
+        Self::from_leaves(&leaves)
+    }
+
+    /// Writes a snapshot of the tree to `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+
+    /// Restores a tree from a snapshot written by `save`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        Self::from_bytes(&bytes)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid commitment tree"))
+    }
+
+    // The roots of empty subtrees at each level
+    fn empty_roots() -> Vec<Digest> {
+        let mut roots = vec![H::blank()];
+        for level in 0..TREE_DEPTH {
+            roots.push(H::combine(&roots[level], &roots[level]));
+        }
+        roots
+    }
+
+    // The value of the first incomplete node at each level, i.e. the node at
+    // index `nodes[level].len()` with its missing leaves set to blank
+    fn frontier(&self) -> Vec<Digest> {
+        let empty_roots = Self::empty_roots();
+        let mut frontier = vec![H::blank()];
+        for level in 0..TREE_DEPTH {
+            let completed = &self.nodes[level];
+            let node = if completed.len() % 2 == 1 {
+                H::combine(&completed[completed.len() - 1], &frontier[level])
+            } else {
+                H::combine(&frontier[level], &empty_roots[level])
+            };
+            frontier.push(node);
+        }
+        frontier
+    }
+}
+
+impl<const TREE_DEPTH: usize, H: HashFunction> Default for CommitmentTree<TREE_DEPTH, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const TREE_DEPTH: usize, H: HashFunction> Serialize for CommitmentTree<TREE_DEPTH, H> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.nodes[0].serialize(serializer)
+    }
+}
+
+impl<'de, const TREE_DEPTH: usize, H: HashFunction> Deserialize<'de>
+    for CommitmentTree<TREE_DEPTH, H>
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let leaves = Vec::<Digest>::deserialize(deserializer)?;
+        Self::from_leaves(&leaves)
+            .ok_or_else(|| serde::de::Error::custom("Too many leaves for CommitmentTree"))
+    }
+}
+
+#[test]
+fn test_commitment_tree() {
+    use crate::action_tree::MerkleTree;
+    use crate::constants::{COMMITMENT_TREE_DEPTH, INITIAL_ROOT};
+
+    let tree = CommitmentTree::<COMMITMENT_TREE_DEPTH>::new();
+    assert_eq!(tree.root(), *INITIAL_ROOT);
+
+    // Compare against the action tree, which has the same depth and padding
+    let mut tree = CommitmentTree::<4>::new();
+    let leaves: Vec<Digest> = (0..11u32).map(|i| Digest::from([i; 8])).collect();
+    for (i, leaf) in leaves.iter().enumerate() {
+        assert_eq!(tree.append(*leaf), Some(i));
+        let action_tree = MerkleTree::new(leaves[..=i].to_vec()).unwrap();
+        assert_eq!(tree.root(), action_tree.root());
+        for leaf in &leaves[..=i] {
+            let path = tree.path(tree.position(leaf).unwrap()).unwrap();
+            assert_eq!(path, action_tree.generate_path::<4>(*leaf).unwrap());
+            assert_eq!(path.root(*leaf), tree.root());
+        }
+    }
+
+    let snapshot = std::env::temp_dir().join("aarm_commitment_tree_snapshot.bin");
+    tree.save(&snapshot).unwrap();
+    let restored = CommitmentTree::<4>::load(&snapshot).unwrap();
+    std::fs::remove_file(&snapshot).unwrap();
+    assert_eq!(restored, tree);
+    assert_eq!(restored.root(), tree.root());
+
+    // Positions refer to the first occurrence of a commitment
+    let mut tree = CommitmentTree::<4>::from_leaves(&leaves[..3]).unwrap();
+    assert_eq!(tree.append(leaves[1]), Some(3));
+    assert_eq!(tree.position(&leaves[1]), Some(1));
+    assert_eq!(tree.position(&leaves[3]), None);
+
+    let mut full_tree = CommitmentTree::<2>::new();
+    for leaf in &leaves[..4] {
+        assert!(full_tree.append(*leaf).is_some());
+    }
+    assert_eq!(full_tree.append(leaves[4]), None);
+    assert_eq!(full_tree.path(3).unwrap().root(leaves[3]), full_tree.root());
+}
+
+#[test]
+fn test_keccak_commitment_tree() {
+    use crate::hasher::Keccak256Hasher;
+
+    let leaves: Vec<Digest> = (0..5u32).map(|i| Digest::from([i; 8])).collect();
+    let sha_tree = CommitmentTree::<4>::from_leaves(&leaves).unwrap();
+    let keccak_tree = CommitmentTree::<4, Keccak256Hasher>::from_leaves(&leaves).unwrap();
+    assert_ne!(keccak_tree.root(), sha_tree.root());
+
+    for (position, leaf) in leaves.iter().enumerate() {
+        let path = keccak_tree.path(position).unwrap();
+        assert_eq!(
+            path.root_with_hasher::<Keccak256Hasher>(*leaf),
+            keccak_tree.root()
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    constants::{INITIAL_ROOT, TRIVIAL_RESOURCE_LOGIC_VK},
+    constants::{DEFAULT_BYTES, INITIAL_ROOT, TRIVIAL_RESOURCE_LOGIC_VK},
     merkle_path::MerklePath,
     nullifier_key::NullifierKey,
     resource::Resource,
@@ -11,7 +11,8 @@ use k256::{
     },
     EncodedPoint, ProjectivePoint, Scalar,
 };
-use risc0_zkvm::sha::{Digest, Impl, Sha256};
+use rand::Rng;
+use risc0_zkvm::sha::{Digest, Impl, Sha256, DIGEST_BYTES};
 
 #[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct ComplianceInstance {
@@ -38,9 +39,7 @@ pub struct ComplianceWitness<const COMMITMENT_TREE_DEPTH: usize> {
     pub created_resource: Resource,
     /// Random scalar for delta commitment
     pub rcv: Scalar,
-    // TODO: If we want to add function privacy, include:
-    // pub input_resource_logic_cm_r: [u8; DATA_BYTES],
-    // pub output_resource_logic_cm_r: [u8; DATA_BYTES],
+    // For function privacy, see `PrivateComplianceWitness`
 }
 
 impl<const COMMITMENT_TREE_DEPTH: usize> ComplianceWitness<COMMITMENT_TREE_DEPTH> {
@@ -203,18 +202,7 @@ impl<const COMMITMENT_TREE_DEPTH: usize> Default for ComplianceWitness<COMMITMEN
 
 impl ComplianceInstance {
     pub fn delta_projective(&self) -> ProjectivePoint {
-        let x: [u8; 32] = self
-            .delta_x
-            .as_bytes()
-            .try_into()
-            .expect("delta_x must be 32 bytes");
-        let y: [u8; 32] = self
-            .delta_y
-            .as_bytes()
-            .try_into()
-            .expect("delta_y must be 32 bytes");
-        let encoded_point = EncodedPoint::from_affine_coordinates(&x.into(), &y.into(), false);
-        ProjectivePoint::from_encoded_point(&encoded_point).unwrap()
+        delta_from_coordinates(&self.delta_x, &self.delta_y)
     }
 
     pub fn delta_msg(&self) -> Vec<u8> {
@@ -225,6 +213,269 @@ impl ComplianceInstance {
     }
 }
 
+/// The function-private compliance instance publishes commitments to the
+/// logic refs instead of the logic refs themselves.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct PrivateComplianceInstance {
+    pub consumed_nullifier: Digest,
+    pub consumed_logic_cm: Digest,
+    pub consumed_commitment_tree_root: Digest,
+    pub created_commitment: Digest,
+    pub created_logic_cm: Digest,
+    pub delta_x: Digest,
+    pub delta_y: Digest,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct PrivateComplianceWitness<const COMMITMENT_TREE_DEPTH: usize> {
+    pub compliance_witness: ComplianceWitness<COMMITMENT_TREE_DEPTH>,
+    /// Randomness of the consumed resource logic commitment
+    pub consumed_logic_cm_r: [u8; DEFAULT_BYTES],
+    /// Randomness of the created resource logic commitment
+    pub created_logic_cm_r: [u8; DEFAULT_BYTES],
+}
+
+/// Commits to a resource logic ref so that it can be hidden from observers.
+pub fn logic_commitment(logic_ref: &Digest, logic_cm_r: &[u8; DEFAULT_BYTES]) -> Digest {
+    let mut bytes = [0u8; DIGEST_BYTES + DEFAULT_BYTES];
+    bytes[..DIGEST_BYTES].clone_from_slice(logic_ref.as_bytes());
+    bytes[DIGEST_BYTES..].clone_from_slice(logic_cm_r);
+    *Impl::hash_bytes(&bytes)
+}
+
+impl<const COMMITMENT_TREE_DEPTH: usize> PrivateComplianceWitness<COMMITMENT_TREE_DEPTH> {
+    pub fn new(compliance_witness: ComplianceWitness<COMMITMENT_TREE_DEPTH>) -> Self {
+        let mut rng = rand::thread_rng();
+        PrivateComplianceWitness {
+            compliance_witness,
+            consumed_logic_cm_r: rng.gen(),
+            created_logic_cm_r: rng.gen(),
+        }
+    }
+
+    pub fn constrain(&self) -> PrivateComplianceInstance {
+        let instance = self.compliance_witness.constrain();
+        PrivateComplianceInstance {
+            consumed_nullifier: instance.consumed_nullifier,
+            consumed_logic_cm: logic_commitment(
+                &instance.consumed_logic_ref,
+                &self.consumed_logic_cm_r,
+            ),
+            consumed_commitment_tree_root: instance.consumed_commitment_tree_root,
+            created_commitment: instance.created_commitment,
+            created_logic_cm: logic_commitment(
+                &instance.created_logic_ref,
+                &self.created_logic_cm_r,
+            ),
+            delta_x: instance.delta_x,
+            delta_y: instance.delta_y,
+        }
+    }
+}
+
+impl PrivateComplianceInstance {
+    pub fn delta_projective(&self) -> ProjectivePoint {
+        delta_from_coordinates(&self.delta_x, &self.delta_y)
+    }
+
+    pub fn delta_msg(&self) -> Vec<u8> {
+        let mut msg = Vec::new();
+        msg.extend_from_slice(self.consumed_nullifier.as_bytes());
+        msg.extend_from_slice(self.created_commitment.as_bytes());
+        msg
+    }
+}
+
+/// A compliance unit with any number of consumed and created resources. The
+/// deltas of all the resources are aggregated into a single delta commitment.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct MultiComplianceInstance {
+    pub consumed_nullifiers: Vec<Digest>,
+    pub consumed_logic_refs: Vec<Digest>,
+    pub consumed_commitment_tree_roots: Vec<Digest>,
+    pub created_commitments: Vec<Digest>,
+    pub created_logic_refs: Vec<Digest>,
+    pub delta_x: Digest,
+    pub delta_y: Digest,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct ConsumedResourceWitness<const COMMITMENT_TREE_DEPTH: usize> {
+    /// The consumed resource
+    pub resource: Resource,
+    /// The path from the consumed commitment to the root in the commitment tree
+    pub merkle_path: MerklePath<COMMITMENT_TREE_DEPTH>,
+    /// Nullifier key of the consumed resource
+    pub nf_key: NullifierKey,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct MultiComplianceWitness<const COMMITMENT_TREE_DEPTH: usize> {
+    /// The consumed resources
+    pub consumed_resources: Vec<ConsumedResourceWitness<COMMITMENT_TREE_DEPTH>>,
+    /// The existing root for the ephemeral resources
+    pub ephemeral_root: Digest,
+    /// The created resources
+    pub created_resources: Vec<Resource>,
+    /// Random scalar for the aggregated delta commitment
+    pub rcv: Scalar,
+}
+
+impl<const COMMITMENT_TREE_DEPTH: usize> ConsumedResourceWitness<COMMITMENT_TREE_DEPTH> {
+    pub fn new(
+        resource: Resource,
+        nf_key: NullifierKey,
+        merkle_path: MerklePath<COMMITMENT_TREE_DEPTH>,
+    ) -> Self {
+        ConsumedResourceWitness {
+            resource,
+            merkle_path,
+            nf_key,
+        }
+    }
+}
+
+impl<const COMMITMENT_TREE_DEPTH: usize> MultiComplianceWitness<COMMITMENT_TREE_DEPTH> {
+    pub fn from_resources(
+        consumed_resources: Vec<ConsumedResourceWitness<COMMITMENT_TREE_DEPTH>>,
+        created_resources: Vec<Resource>,
+    ) -> Self {
+        let rng = rand::thread_rng();
+        MultiComplianceWitness {
+            consumed_resources,
+            ephemeral_root: *INITIAL_ROOT,
+            created_resources,
+            rcv: Scalar::random(rng),
+        }
+    }
+
+    pub fn constrain(&self) -> MultiComplianceInstance {
+        assert!(
+            !self.consumed_resources.is_empty() || !self.created_resources.is_empty(),
+            "A compliance unit must contain at least one resource"
+        );
+
+        let mut instance = MultiComplianceInstance::default();
+        for consumed in &self.consumed_resources {
+            let cm = consumed.resource.commitment();
+            let root = if consumed.resource.is_ephemeral {
+                self.ephemeral_root
+            } else {
+                consumed.merkle_path.root(cm)
+            };
+            let nullifier = consumed
+                .resource
+                .nullifier_from_commitment(&consumed.nf_key, &cm)
+                .unwrap();
+
+            instance.consumed_nullifiers.push(nullifier);
+            instance
+                .consumed_logic_refs
+                .push(consumed.resource.logic_ref);
+            instance.consumed_commitment_tree_roots.push(root);
+        }
+
+        for created in &self.created_resources {
+            instance.created_commitments.push(created.commitment());
+            instance.created_logic_refs.push(created.logic_ref);
+        }
+
+        (instance.delta_x, instance.delta_y) = self.delta_commitment();
+        instance
+    }
+
+    pub fn delta_commitment(&self) -> (Digest, Digest) {
+        // Aggregate the deltas of all the resources
+        let consumed = self
+            .consumed_resources
+            .iter()
+            .fold(ProjectivePoint::IDENTITY, |acc, consumed| {
+                acc + consumed.resource.kind() * consumed.resource.quantity_scalar()
+            });
+        let created = self
+            .created_resources
+            .iter()
+            .fold(ProjectivePoint::IDENTITY, |acc, created| {
+                acc + created.kind() * created.quantity_scalar()
+            });
+        let delta = consumed - created + ProjectivePoint::GENERATOR * self.rcv;
+
+        let encoded_delta = delta.to_encoded_point(false);
+        (
+            Digest::try_from(&encoded_delta.x().unwrap()[..]).unwrap(),
+            Digest::try_from(&encoded_delta.y().unwrap()[..]).unwrap(),
+        )
+    }
+}
+
+impl MultiComplianceInstance {
+    /// Returns the tags of the unit in action tree order: the consumed
+    /// nullifiers followed by the created commitments.
+    pub fn tags(&self) -> Vec<Digest> {
+        let mut tags = self.consumed_nullifiers.clone();
+        tags.extend_from_slice(&self.created_commitments);
+        tags
+    }
+
+    /// Returns whether each tag is a nullifier, in the same order as `tags`.
+    pub fn is_consumed(&self) -> Vec<bool> {
+        let mut is_consumed = vec![true; self.consumed_nullifiers.len()];
+        is_consumed.resize(is_consumed.len() + self.created_commitments.len(), false);
+        is_consumed
+    }
+
+    /// Returns the logic refs in the same order as `tags`.
+    pub fn logic_refs(&self) -> Vec<Digest> {
+        let mut logic_refs = self.consumed_logic_refs.clone();
+        logic_refs.extend_from_slice(&self.created_logic_refs);
+        logic_refs
+    }
+
+    pub fn delta_projective(&self) -> ProjectivePoint {
+        delta_from_coordinates(&self.delta_x, &self.delta_y)
+    }
+
+    pub fn delta_msg(&self) -> Vec<u8> {
+        let mut msg = Vec::new();
+        for nullifier in &self.consumed_nullifiers {
+            msg.extend_from_slice(nullifier.as_bytes());
+        }
+        for commitment in &self.created_commitments {
+            msg.extend_from_slice(commitment.as_bytes());
+        }
+        msg
+    }
+}
+
+/// A single compliance unit is the multi-resource unit with one consumed and
+/// one created resource.
+impl From<ComplianceInstance> for MultiComplianceInstance {
+    fn from(instance: ComplianceInstance) -> Self {
+        MultiComplianceInstance {
+            consumed_nullifiers: vec![instance.consumed_nullifier],
+            consumed_logic_refs: vec![instance.consumed_logic_ref],
+            consumed_commitment_tree_roots: vec![instance.consumed_commitment_tree_root],
+            created_commitments: vec![instance.created_commitment],
+            created_logic_refs: vec![instance.created_logic_ref],
+            delta_x: instance.delta_x,
+            delta_y: instance.delta_y,
+        }
+    }
+}
+
+fn delta_from_coordinates(delta_x: &Digest, delta_y: &Digest) -> ProjectivePoint {
+    let x: [u8; 32] = delta_x
+        .as_bytes()
+        .try_into()
+        .expect("delta_x must be 32 bytes");
+    let y: [u8; 32] = delta_y
+        .as_bytes()
+        .try_into()
+        .expect("delta_y must be 32 bytes");
+    let encoded_point = EncodedPoint::from_affine_coordinates(&x.into(), &y.into(), false);
+    ProjectivePoint::from_encoded_point(&encoded_point).unwrap()
+}
+
 #[test]
 fn test_compliance_instance_encoding() {
     let instance = ComplianceInstance::default();
@@ -233,3 +484,59 @@ fn test_compliance_instance_encoding() {
     println!("Encoded instance: {:?}", encoded);
     assert!(encoded.len() == 224);
 }
+
+#[test]
+fn test_multi_compliance_single_unit() {
+    // A unit with one consumed and one created resource matches the single unit
+    let witness = ComplianceWitness::<4>::default();
+    let multi_witness = MultiComplianceWitness::<4> {
+        consumed_resources: vec![ConsumedResourceWitness::new(
+            witness.consumed_resource,
+            witness.nf_key,
+            witness.merkle_path,
+        )],
+        ephemeral_root: witness.ephemeral_root,
+        created_resources: vec![witness.created_resource],
+        rcv: witness.rcv,
+    };
+
+    let instance = MultiComplianceInstance::from(witness.constrain());
+    let multi_instance = multi_witness.constrain();
+    assert_eq!(multi_instance.tags(), instance.tags());
+    assert_eq!(multi_instance.logic_refs(), instance.logic_refs());
+    assert_eq!(
+        multi_instance.consumed_commitment_tree_roots,
+        instance.consumed_commitment_tree_roots
+    );
+    assert_eq!(
+        multi_instance.delta_projective(),
+        instance.delta_projective()
+    );
+    assert_eq!(multi_instance.delta_msg(), instance.delta_msg());
+}
+
+#[test]
+fn test_private_compliance_hides_logic_refs() {
+    let witness = PrivateComplianceWitness::new(ComplianceWitness::<4>::default());
+    let instance = witness.compliance_witness.constrain();
+    let private_instance = witness.constrain();
+
+    assert_ne!(
+        private_instance.consumed_logic_cm,
+        instance.consumed_logic_ref
+    );
+    assert_eq!(
+        private_instance.consumed_logic_cm,
+        logic_commitment(&instance.consumed_logic_ref, &witness.consumed_logic_cm_r)
+    );
+    assert_eq!(
+        private_instance.created_logic_cm,
+        logic_commitment(&instance.created_logic_ref, &witness.created_logic_cm_r)
+    );
+    // Both resources share the logic, yet the commitments are unlinkable
+    assert_ne!(
+        private_instance.consumed_logic_cm,
+        private_instance.created_logic_cm
+    );
+    assert_eq!(private_instance.delta_msg(), instance.delta_msg());
+}
@@ -1,36 +1,63 @@
 use k256::ecdsa::{Error, RecoveryId, Signature, SigningKey, VerifyingKey};
-use k256::{elliptic_curve::ScalarPrimitive, ProjectivePoint, PublicKey, Scalar, SecretKey};
+use k256::{
+    elliptic_curve::{
+        ops::Reduce,
+        sec1::{FromEncodedPoint, ToEncodedPoint},
+        Field, PrimeField,
+    },
+    EncodedPoint, FieldBytes, NonZeroScalar, ProjectivePoint, PublicKey, Scalar, U256,
+};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use sha3::{Digest, Keccak256};
 
-#[derive(Clone, Debug)]
-pub struct DeltaProof {
-    pub signature: Signature,
-    pub recid: RecoveryId,
+const SCHNORR_NONCE_DOMAIN: &[u8] = b"AARM_DELTA_SCHNORR_NONCE";
+const SCHNORR_CHALLENGE_DOMAIN: &[u8] = b"AARM_DELTA_SCHNORR_CHALLENGE";
+const SCHNORR_AGGREGATION_DOMAIN: &[u8] = b"AARM_DELTA_SCHNORR_AGGREGATION";
+
+/// A signature with the delta witness over the delta message.
+///
+/// When the `rcv`s of a transaction cancel out, the witness is zero and has
+/// no signing key. If the delta is then the identity, the transaction is
+/// balanced without any signature and its proof is `Zero`, encoded as 65
+/// zero bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeltaProof {
+    Signature {
+        signature: Signature,
+        recid: RecoveryId,
+    },
+    Zero,
 }
 
 #[derive(Clone, Debug)]
 pub struct DeltaWitness {
-    pub signing_key: SigningKey,
+    /// `None` if the `rcv`s sum to zero
+    pub signing_key: Option<SigningKey>,
 }
 
+#[derive(Clone, Debug)]
 pub struct DeltaInstance {
-    pub verifying_key: VerifyingKey,
+    /// `None` if the deltas sum to the identity
+    pub verifying_key: Option<VerifyingKey>,
 }
 
 impl DeltaProof {
     pub fn prove(message: &[u8], witness: &DeltaWitness) -> DeltaProof {
+        let Some(signing_key) = &witness.signing_key else {
+            return DeltaProof::Zero;
+        };
+
         // Hash the message using Keccak256
         let mut digest = Keccak256::new();
         digest.update(message);
 
         // Sign the hashed message using RFC6979
-        let (signature, recid) = witness
-            .signing_key
+        let (signature, recid) = signing_key
             .sign_digest_recoverable(digest)
             .expect("Failed to sign message");
 
-        DeltaProof { signature, recid }
+        DeltaProof::Signature { signature, recid }
     }
 
     pub fn verify(
@@ -38,58 +65,117 @@ impl DeltaProof {
         proof: &DeltaProof,
         instance: DeltaInstance,
     ) -> Result<bool, Error> {
+        let (signature, recid, verifying_key) = match (proof, instance.verifying_key) {
+            (DeltaProof::Signature { signature, recid }, Some(verifying_key)) => {
+                (signature, *recid, verifying_key)
+            }
+            // The sum of the deltas can only be the identity if the
+            // quantities balance and the rcvs cancel out, barring a known
+            // discrete log relation between the kinds, so there is nothing
+            // left to sign
+            (DeltaProof::Zero, None) => return Ok(true),
+            _ => return Ok(false),
+        };
+
         // Hash the message using Keccak256
         let mut digest = Keccak256::new();
         digest.update(message);
 
         // Verify the signature
-        let vk = VerifyingKey::recover_from_digest(digest, &proof.signature, proof.recid)?;
-        Ok(vk == instance.verifying_key)
+        let vk = VerifyingKey::recover_from_digest(digest, signature, recid)?;
+        Ok(vk == verifying_key)
+    }
+
+    /// Verifies many delta proofs and returns the indices of the invalid
+    /// ones. ECDSA has no batch equation, see `SchnorrDeltaProof` for that.
+    pub fn verify_batch(items: &[(&[u8], &DeltaProof, &DeltaInstance)]) -> Vec<usize> {
+        items
+            .iter()
+            .enumerate()
+            .filter(|(_, (message, proof, instance))| {
+                !matches!(
+                    DeltaProof::verify(message, proof, (*instance).clone()),
+                    Ok(true)
+                )
+            })
+            .map(|(index, _)| index)
+            .collect()
     }
 
     pub fn to_bytes(&self) -> [u8; 65] {
         let mut bytes = [0u8; 65];
-        bytes[0..64].clone_from_slice(&self.signature.to_bytes());
-        bytes[64] = self.recid.to_byte() + 27;
+        if let DeltaProof::Signature { signature, recid } = self {
+            bytes[0..64].clone_from_slice(&signature.to_bytes());
+            bytes[64] = recid.to_byte() + 27;
+        }
         bytes
     }
 
+    #[deprecated(note = "use `DeltaProof::try_from`, which returns an error on invalid bytes")]
     pub fn from_bytes(bytes: &[u8]) -> DeltaProof {
-        DeltaProof {
-            signature: Signature::from_bytes((&bytes[0..64]).into()).unwrap(),
-            recid: RecoveryId::from_byte(bytes[64] - 27).unwrap(),
+        DeltaProof::try_from(bytes).expect("invalid delta proof")
+    }
+}
+
+impl TryFrom<&[u8]> for DeltaProof {
+    type Error = DeltaError;
+
+    /// Parses `r || s || v`, where `v` is the recovery ID either as is or
+    /// plus 27 as in Ethereum, or 65 zero bytes for the zero proof.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        check_length(bytes, 65)?;
+        if bytes.iter().all(|byte| *byte == 0) {
+            return Ok(DeltaProof::Zero);
         }
+        let recid = match bytes[64] {
+            v @ (0 | 1) => v,
+            v @ (27 | 28) => v - 27,
+            v => return Err(DeltaError::InvalidRecoveryId(v)),
+        };
+        Ok(DeltaProof::Signature {
+            signature: Signature::from_bytes((&bytes[0..64]).into())
+                .map_err(|_| DeltaError::InvalidSignature)?,
+            recid: RecoveryId::from_byte(recid).ok_or(DeltaError::InvalidRecoveryId(recid))?,
+        })
     }
 }
 
 impl DeltaWitness {
     pub fn from_scalars(secret_keys: &[Scalar]) -> DeltaWitness {
-        let sum: ScalarPrimitive<_> = secret_keys
-            .iter()
-            .fold(Scalar::ZERO, |acc, x| acc + x)
-            .into();
-        let sk: SecretKey = SecretKey::new(sum);
-        let signing_key = SigningKey::from(&sk);
-        DeltaWitness { signing_key }
+        Self::from_scalar(secret_keys.iter().fold(Scalar::ZERO, |acc, x| acc + x))
     }
 
-    pub fn from_bytes(bytes: &[u8]) -> DeltaWitness {
+    fn from_scalar(scalar: Scalar) -> DeltaWitness {
+        let signing_key: Option<NonZeroScalar> = NonZeroScalar::new(scalar).into();
         DeltaWitness {
-            signing_key: SigningKey::from_bytes(bytes.into()).unwrap(),
+            signing_key: signing_key.map(SigningKey::from),
         }
     }
 
+    /// The sum of the `rcv`s.
+    pub fn scalar(&self) -> Scalar {
+        self.signing_key
+            .as_ref()
+            .map_or(Scalar::ZERO, |key| *key.as_nonzero_scalar().as_ref())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.signing_key.is_none()
+    }
+
+    /// The 32 bytes of the signing key, or zeros for a zero witness.
     pub fn to_bytes(&self) -> [u8; 32] {
-        self.signing_key.to_bytes().into()
+        self.scalar().to_bytes().into()
+    }
+
+    /// The randomness part of the delta, i.e. the sum of the `rcv`s times
+    /// the generator.
+    pub fn commitment(&self) -> ProjectivePoint {
+        ProjectivePoint::GENERATOR * self.scalar()
     }
 
     pub fn compose(&self, other: &DeltaWitness) -> Self {
-        let sum = self.signing_key.as_nonzero_scalar().as_ref()
-            + other.signing_key.as_nonzero_scalar().as_ref();
-        let sk: SecretKey = SecretKey::new(sum.into());
-        Self {
-            signing_key: SigningKey::from(sk),
-        }
+        Self::from_scalar(self.scalar() + other.scalar())
     }
 
     pub fn compress(witnesses: &[DeltaWitness]) -> DeltaWitness {
@@ -101,15 +187,267 @@ impl DeltaWitness {
     }
 }
 
+impl TryFrom<&[u8]> for DeltaWitness {
+    type Error = DeltaError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        check_length(bytes, 32)?;
+        if bytes.iter().all(|byte| *byte == 0) {
+            return Ok(DeltaWitness { signing_key: None });
+        }
+        Ok(DeltaWitness {
+            signing_key: Some(
+                SigningKey::from_bytes(bytes.into()).map_err(|_| DeltaError::InvalidSigningKey)?,
+            ),
+        })
+    }
+}
+
 impl DeltaInstance {
-    pub fn from_deltas(deltas: &[ProjectivePoint]) -> Result<DeltaInstance, Error> {
+    /// Sums the deltas of a transaction.
+    ///
+    /// A sum to the identity is not an error: it is the instance of a
+    /// balanced transaction whose `rcv`s cancel out, which has no verifying
+    /// key and is only verified by `DeltaProof::Zero`, while any signature
+    /// is rejected against it. Check `is_identity` to tell it apart.
+    pub fn from_deltas(deltas: &[ProjectivePoint]) -> DeltaInstance {
         let sum = deltas
             .iter()
             .fold(ProjectivePoint::IDENTITY, |acc, x| acc + x);
-        let pk = PublicKey::from_affine(sum.to_affine()).unwrap();
-        let vk = VerifyingKey::from(&pk);
-        Ok(DeltaInstance { verifying_key: vk })
+        // The identity is not a valid public key
+        let pk = PublicKey::from_affine(sum.to_affine()).ok();
+        DeltaInstance {
+            verifying_key: pk.as_ref().map(VerifyingKey::from),
+        }
     }
+
+    pub fn is_identity(&self) -> bool {
+        self.verifying_key.is_none()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeltaError {
+    InvalidLength { expected: usize, found: usize },
+    InvalidSignature,
+    InvalidRecoveryId(u8),
+    InvalidSigningKey,
+    InvalidPoint,
+    InvalidScalar,
+}
+
+impl std::fmt::Display for DeltaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeltaError::InvalidLength { expected, found } => {
+                write!(f, "expected {expected} bytes, found {found}")
+            }
+            DeltaError::InvalidSignature => write!(f, "invalid signature"),
+            DeltaError::InvalidRecoveryId(v) => write!(f, "invalid recovery ID {v}"),
+            DeltaError::InvalidSigningKey => write!(f, "invalid signing key"),
+            DeltaError::InvalidPoint => write!(f, "invalid curve point"),
+            DeltaError::InvalidScalar => write!(f, "invalid scalar"),
+        }
+    }
+}
+
+impl std::error::Error for DeltaError {}
+
+fn check_length(bytes: &[u8], expected: usize) -> Result<(), DeltaError> {
+    if bytes.len() != expected {
+        return Err(DeltaError::InvalidLength {
+            expected,
+            found: bytes.len(),
+        });
+    }
+    Ok(())
+}
+
+/// A Schnorr signature over secp256k1 with the delta witness, an
+/// alternative to the ECDSA `DeltaProof` that can be batch verified and
+/// aggregated.
+///
+/// The challenge is `Keccak256(AARM_DELTA_SCHNORR_CHALLENGE || R || P || msg)`
+/// reduced modulo the group order, where `R` and the delta `P` are
+/// compressed points, and `s * G = R + e * P`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SchnorrDeltaProof {
+    pub r: ProjectivePoint,
+    pub s: Scalar,
+}
+
+/// Schnorr delta proofs half-aggregated into their nonces and a single
+/// weighted sum of their `s`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AggregateSchnorrDeltaProof {
+    pub rs: Vec<ProjectivePoint>,
+    pub s: Scalar,
+}
+
+impl SchnorrDeltaProof {
+    pub fn prove(message: &[u8], witness: &DeltaWitness) -> SchnorrDeltaProof {
+        let x = witness.scalar();
+        let p = ProjectivePoint::GENERATOR * x;
+        // Derive the nonce from the key and the message, with fresh
+        // randomness against fault attacks
+        let aux: [u8; 32] = rand::thread_rng().gen();
+        let k = hash_to_scalar(&[SCHNORR_NONCE_DOMAIN, x.to_bytes().as_slice(), &aux, message]);
+        let r = ProjectivePoint::GENERATOR * k;
+        let e = schnorr_challenge(&r, &p, message);
+        SchnorrDeltaProof { r, s: k + e * x }
+    }
+
+    pub fn verify(message: &[u8], proof: &SchnorrDeltaProof, instance: &DeltaInstance) -> bool {
+        let p = instance_point(instance);
+        ProjectivePoint::GENERATOR * proof.s
+            == proof.r + p * schnorr_challenge(&proof.r, &p, message)
+    }
+
+    /// Verifies many proofs with a single randomly weighted equation and, if
+    /// it fails, returns the indices of the invalid proofs.
+    pub fn verify_batch(items: &[(&[u8], &SchnorrDeltaProof, &DeltaInstance)]) -> Vec<usize> {
+        let mut rng = rand::thread_rng();
+        let mut s = Scalar::ZERO;
+        let mut rhs = ProjectivePoint::IDENTITY;
+        for (index, (message, proof, instance)) in items.iter().enumerate() {
+            let weight = if index == 0 {
+                Scalar::ONE
+            } else {
+                Scalar::random(&mut rng)
+            };
+            let p = instance_point(instance);
+            let e = schnorr_challenge(&proof.r, &p, message);
+            s += weight * proof.s;
+            rhs += (proof.r + p * e) * weight;
+        }
+        if ProjectivePoint::GENERATOR * s == rhs {
+            return vec![];
+        }
+        items
+            .iter()
+            .enumerate()
+            .filter(|(_, (message, proof, instance))| {
+                !SchnorrDeltaProof::verify(message, proof, instance)
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    pub fn to_bytes(&self) -> [u8; 65] {
+        let mut bytes = [0u8; 65];
+        bytes[..33].copy_from_slice(&point_bytes(&self.r));
+        bytes[33..].copy_from_slice(&self.s.to_bytes());
+        bytes
+    }
+}
+
+impl TryFrom<&[u8]> for SchnorrDeltaProof {
+    type Error = DeltaError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        check_length(bytes, 65)?;
+        let r = EncodedPoint::from_bytes(&bytes[..33]).map_err(|_| DeltaError::InvalidPoint)?;
+        Ok(SchnorrDeltaProof {
+            r: Option::from(ProjectivePoint::from_encoded_point(&r))
+                .ok_or(DeltaError::InvalidPoint)?,
+            s: Option::from(Scalar::from_repr(*FieldBytes::from_slice(&bytes[33..])))
+                .ok_or(DeltaError::InvalidScalar)?,
+        })
+    }
+}
+
+impl AggregateSchnorrDeltaProof {
+    /// Aggregates valid proofs, e.g. of the transactions of a block. The
+    /// messages and instances are needed again for verification.
+    pub fn aggregate(items: &[(&[u8], &SchnorrDeltaProof, &DeltaInstance)]) -> Self {
+        let rs = items
+            .iter()
+            .map(|(_, proof, _)| proof.r)
+            .collect::<Vec<_>>();
+        let weights = aggregation_weights(
+            &rs,
+            items
+                .iter()
+                .map(|(message, _, instance)| (*message, *instance)),
+        );
+        let s = items
+            .iter()
+            .zip(weights)
+            .fold(Scalar::ZERO, |acc, ((_, proof, _), weight)| {
+                acc + weight * proof.s
+            });
+        AggregateSchnorrDeltaProof { rs, s }
+    }
+
+    pub fn verify(&self, items: &[(&[u8], &DeltaInstance)]) -> bool {
+        if items.len() != self.rs.len() {
+            return false;
+        }
+        let weights = aggregation_weights(&self.rs, items.iter().copied());
+        let rhs = items.iter().zip(&self.rs).zip(weights).fold(
+            ProjectivePoint::IDENTITY,
+            |acc, (((message, instance), r), weight)| {
+                let p = instance_point(instance);
+                acc + (*r + p * schnorr_challenge(r, &p, message)) * weight
+            },
+        );
+        ProjectivePoint::GENERATOR * self.s == rhs
+    }
+}
+
+// The weight of each proof commits to all the nonces, instances and messages
+fn aggregation_weights<'a>(
+    rs: &[ProjectivePoint],
+    items: impl Iterator<Item = (&'a [u8], &'a DeltaInstance)>,
+) -> Vec<Scalar> {
+    let mut transcript = Keccak256::new();
+    transcript.update(SCHNORR_AGGREGATION_DOMAIN);
+    for (r, (message, instance)) in rs.iter().zip(items) {
+        transcript.update(point_bytes(r));
+        transcript.update(point_bytes(&instance_point(instance)));
+        transcript.update((message.len() as u64).to_be_bytes());
+        transcript.update(message);
+    }
+    let transcript = transcript.finalize();
+    (0..rs.len() as u32)
+        .map(|index| hash_to_scalar(&[transcript.as_slice(), &index.to_be_bytes()]))
+        .collect()
+}
+
+fn schnorr_challenge(r: &ProjectivePoint, p: &ProjectivePoint, message: &[u8]) -> Scalar {
+    hash_to_scalar(&[
+        SCHNORR_CHALLENGE_DOMAIN,
+        &point_bytes(r),
+        &point_bytes(p),
+        message,
+    ])
+}
+
+fn hash_to_scalar(parts: &[&[u8]]) -> Scalar {
+    let mut hasher = Keccak256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    <Scalar as Reduce<U256>>::reduce_bytes(&hasher.finalize())
+}
+
+fn instance_point(instance: &DeltaInstance) -> ProjectivePoint {
+    instance
+        .verifying_key
+        .as_ref()
+        .map_or(ProjectivePoint::IDENTITY, |key| {
+            ProjectivePoint::from(*key.as_affine())
+        })
+}
+
+// The compressed encoding, or zeros for the identity
+fn point_bytes(point: &ProjectivePoint) -> [u8; 33] {
+    let mut bytes = [0u8; 33];
+    let encoded = point.to_affine().to_encoded_point(true);
+    if !encoded.is_identity() {
+        bytes.copy_from_slice(encoded.as_bytes());
+    }
+    bytes
 }
 
 impl Serialize for DeltaProof {
@@ -127,12 +465,7 @@ impl<'de> Deserialize<'de> for DeltaProof {
         D: serde::Deserializer<'de>,
     {
         let bytes: Vec<u8> = Vec::deserialize(deserializer)?;
-        if bytes.len() != 65 {
-            return Err(serde::de::Error::custom(
-                "Invalid byte length for DeltaProof",
-            ));
-        }
-        Ok(DeltaProof::from_bytes(&bytes))
+        DeltaProof::try_from(bytes.as_slice()).map_err(serde::de::Error::custom)
     }
 }
 
@@ -150,8 +483,9 @@ impl<'de> Deserialize<'de> for DeltaWitness {
     where
         D: serde::Deserializer<'de>,
     {
-        let bytes = <[u8; 32]>::deserialize(deserializer)?;
-        Ok(DeltaWitness::from_bytes(&bytes))
+        // Matches `serialize_bytes` in `serialize`
+        let bytes: Vec<u8> = Vec::deserialize(deserializer)?;
+        DeltaWitness::try_from(bytes.as_slice()).map_err(serde::de::Error::custom)
     }
 }
 
@@ -164,9 +498,148 @@ fn test_delta_proof() {
     let verifying_key = VerifyingKey::from(&signing_key);
 
     let message = b"Hello, world!";
-    let witness = DeltaWitness { signing_key };
+    let witness = DeltaWitness {
+        signing_key: Some(signing_key),
+    };
     let proof = DeltaProof::prove(message, &witness);
-    let instance = DeltaInstance { verifying_key };
+    let instance = DeltaInstance {
+        verifying_key: Some(verifying_key),
+    };
 
     assert!(DeltaProof::verify(message, &proof, instance).unwrap());
 }
+
+#[test]
+fn test_schnorr_delta_proof() {
+    let witnesses = (1..4u64)
+        .map(|i| DeltaWitness::from_scalars(&[Scalar::from(i)]))
+        .collect::<Vec<_>>();
+    let instances = witnesses
+        .iter()
+        .map(|witness| DeltaInstance::from_deltas(&[witness.commitment()]))
+        .collect::<Vec<_>>();
+    let messages: [&[u8]; 3] = [b"tx0", b"tx1", b"tx2"];
+    let proofs = witnesses
+        .iter()
+        .zip(messages)
+        .map(|(witness, message)| SchnorrDeltaProof::prove(message, witness))
+        .collect::<Vec<_>>();
+
+    let proof = SchnorrDeltaProof::try_from(&proofs[0].to_bytes()[..]).unwrap();
+    assert!(SchnorrDeltaProof::verify(
+        messages[0],
+        &proof,
+        &instances[0]
+    ));
+    assert!(!SchnorrDeltaProof::verify(
+        messages[1],
+        &proof,
+        &instances[0]
+    ));
+
+    let mut items = messages
+        .iter()
+        .zip(&proofs)
+        .zip(&instances)
+        .map(|((message, proof), instance)| (*message, proof, instance))
+        .collect::<Vec<_>>();
+    assert!(SchnorrDeltaProof::verify_batch(&items).is_empty());
+    let aggregate = AggregateSchnorrDeltaProof::aggregate(&items);
+    let public = items
+        .iter()
+        .map(|(message, _, instance)| (*message, *instance))
+        .collect::<Vec<_>>();
+    assert!(aggregate.verify(&public));
+    assert!(!aggregate.verify(&public[..2]));
+
+    items[1].0 = &b"forged"[..];
+    assert_eq!(SchnorrDeltaProof::verify_batch(&items), vec![1]);
+    let forged = items
+        .iter()
+        .map(|(message, _, instance)| (*message, *instance))
+        .collect::<Vec<_>>();
+    assert!(!aggregate.verify(&forged));
+
+    // The ECDSA batch API reports the same indices
+    let ecdsa_proofs = witnesses
+        .iter()
+        .zip(messages)
+        .map(|(witness, message)| DeltaProof::prove(message, witness))
+        .collect::<Vec<_>>();
+    let ecdsa_items = items
+        .iter()
+        .zip(&ecdsa_proofs)
+        .map(|((message, _, instance), proof)| (*message, proof, *instance))
+        .collect::<Vec<_>>();
+    assert_eq!(DeltaProof::verify_batch(&ecdsa_items), vec![1]);
+}
+
+#[test]
+fn test_delta_byte_parsing() {
+    let witness = DeltaWitness::from_scalars(&[Scalar::ONE]);
+    let mut bytes = DeltaProof::prove(b"msg", &witness).to_bytes();
+    let instance = DeltaInstance::from_deltas(&[witness.commitment()]);
+    #[allow(deprecated)]
+    let shim = DeltaProof::from_bytes(&bytes);
+    assert_eq!(DeltaProof::try_from(&bytes[..]).unwrap(), shim);
+
+    // Both recovery ID encodings
+    for v in [bytes[64], bytes[64] - 27] {
+        bytes[64] = v;
+        let proof = DeltaProof::try_from(&bytes[..]).unwrap();
+        assert!(DeltaProof::verify(b"msg", &proof, instance.clone()).unwrap());
+    }
+    bytes[64] = 26;
+    assert_eq!(
+        DeltaProof::try_from(&bytes[..]).unwrap_err(),
+        DeltaError::InvalidRecoveryId(26)
+    );
+    assert_eq!(
+        DeltaProof::try_from(&bytes[..64]).unwrap_err(),
+        DeltaError::InvalidLength {
+            expected: 65,
+            found: 64
+        }
+    );
+    bytes[0..32].fill(0xff);
+    bytes[64] = 27;
+    assert!(bincode::deserialize::<DeltaProof>(&bincode::serialize(&bytes[..]).unwrap()).is_err());
+
+    // Not below the group order
+    assert_eq!(
+        DeltaWitness::try_from(&[0xffu8; 32][..]).unwrap_err(),
+        DeltaError::InvalidSigningKey
+    );
+}
+
+#[test]
+fn test_zero_delta() {
+    // Partial transactions whose rcvs cancel out
+    let x = Scalar::from(7u64);
+    let witness = DeltaWitness::from_scalars(&[x]).compose(&DeltaWitness::from_scalars(&[-x]));
+    assert!(witness.is_zero());
+    assert!(DeltaWitness::from_scalars(&[x, -x]).is_zero());
+    assert_eq!(witness.commitment(), ProjectivePoint::IDENTITY);
+    assert_eq!(witness.to_bytes(), [0u8; 32]);
+    assert!(DeltaWitness::try_from(&witness.to_bytes()[..])
+        .unwrap()
+        .is_zero());
+
+    let instance = DeltaInstance::from_deltas(&[witness.commitment()]);
+    assert!(instance.is_identity());
+    let proof = DeltaProof::prove(b"msg", &witness);
+    assert_eq!(proof, DeltaProof::Zero);
+    assert_eq!(proof.to_bytes(), [0u8; 65]);
+    let decoded: DeltaProof = bincode::deserialize(&bincode::serialize(&proof).unwrap()).unwrap();
+    assert!(DeltaProof::verify(b"msg", &decoded, instance.clone()).unwrap());
+
+    // The zero proof and signatures are not interchangeable
+    let other = DeltaWitness::from_scalars(&[x]);
+    let other_instance = DeltaInstance::from_deltas(&[other.commitment()]);
+    assert!(!DeltaProof::verify(b"msg", &proof, other_instance).unwrap());
+    let signature = DeltaProof::prove(b"msg", &other);
+    assert!(!DeltaProof::verify(b"msg", &signature, instance.clone()).unwrap());
+
+    let schnorr = SchnorrDeltaProof::prove(b"msg", &witness);
+    assert!(SchnorrDeltaProof::verify(b"msg", &schnorr, &instance));
+}
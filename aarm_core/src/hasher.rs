@@ -0,0 +1,42 @@
+use crate::constants::PADDING_LEAVE;
+use risc0_zkvm::sha::{Digest, Impl, Sha256, DIGEST_BYTES};
+use sha3::{Digest as _, Keccak256};
+
+/// The hash function of Merkle trees, resource commitments and nullifiers.
+pub trait HashFunction {
+    fn hash_bytes(bytes: &[u8]) -> Digest;
+
+    /// Returns the parent node within the tree of the two given nodes.
+    fn combine(lhs: &Digest, rhs: &Digest) -> Digest {
+        let mut bytes = [0u8; 2 * DIGEST_BYTES];
+        bytes[..DIGEST_BYTES].clone_from_slice(lhs.as_ref());
+        bytes[DIGEST_BYTES..].clone_from_slice(rhs.as_ref());
+        Self::hash_bytes(&bytes)
+    }
+
+    /// Returns a blank leaf node.
+    fn blank() -> Digest {
+        *PADDING_LEAVE
+    }
+}
+
+/// SHA-256, accelerated inside the zkVM. This is the default everywhere.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Sha256Hasher;
+
+impl HashFunction for Sha256Hasher {
+    fn hash_bytes(bytes: &[u8]) -> Digest {
+        *Impl::hash_bytes(bytes)
+    }
+}
+
+/// Keccak-256, to match the trees and commitments computed on EVM chains.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Keccak256Hasher;
+
+impl HashFunction for Keccak256Hasher {
+    fn hash_bytes(bytes: &[u8]) -> Digest {
+        let hash: [u8; DIGEST_BYTES] = Keccak256::digest(bytes).into();
+        Digest::from(hash)
+    }
+}
@@ -1,11 +1,15 @@
 pub mod action_tree;
+pub mod aggregation;
 pub mod authorization;
+pub mod commitment_tree;
 pub mod compliance;
 pub mod constants;
 pub mod delta_proof;
 pub mod encryption;
+pub mod hasher;
 pub mod logic_instance;
 pub mod merkle_path;
 pub mod nullifier_key;
 pub mod resource;
 pub mod resource_logic;
+pub mod sparse_merkle_tree;
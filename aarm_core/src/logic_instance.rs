@@ -1,4 +1,5 @@
-use risc0_zkvm::sha::Digest;
+use crate::{compliance::logic_commitment, constants::DEFAULT_BYTES};
+use risc0_zkvm::sha::{Digest, Impl, Sha256};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -6,12 +7,83 @@ pub struct LogicInstance {
     pub tag: Digest,
     pub is_consumed: bool,
     pub root: Digest,
+    // The depth of the action tree the existence path of the tag was
+    // checked against
+    pub action_tree_depth: u32,
     pub cipher: Vec<u8>,
     pub app_data: Vec<ExpirableBlob>,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ExpirableBlob {
     pub blob: Vec<u8>,
     pub deletion_criterion: u8,
 }
+
+const FORWARDER_CALL_DOMAIN: &[u8] = b"AARM_FORWARDER_CALL_V1";
+
+/// Binds a forwarder call to the resource carrying it, given the tag and the
+/// commitment of the resource. The resource logic authorizes the call by
+/// adding the binding over its own tag and commitment to its app data, where
+/// `Action::verify` looks for it. A consumed resource is thus tied to its
+/// nullifier without revealing its nullifier key.
+pub fn forwarder_call_binding(
+    tag: &Digest,
+    commitment: &Digest,
+    untrusted_forwarder: &[u8; 20],
+    input: &[u8],
+    output: &[u8],
+) -> ExpirableBlob {
+    let mut bytes = FORWARDER_CALL_DOMAIN.to_vec();
+    bytes.extend_from_slice(tag.as_bytes());
+    bytes.extend_from_slice(commitment.as_bytes());
+    bytes.extend_from_slice(untrusted_forwarder);
+    for data in [input, output] {
+        bytes.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(data);
+    }
+    ExpirableBlob {
+        blob: Impl::hash_bytes(&bytes).as_bytes().to_vec(),
+        deletion_criterion: 0,
+    }
+}
+
+/// The public inputs of a wrapped logic proof: the logic ref is replaced by
+/// the commitment published in the function-private compliance instance.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PrivateLogicInstance {
+    pub logic_cm: Digest,
+    pub tag: Digest,
+    pub is_consumed: bool,
+    pub root: Digest,
+    pub action_tree_depth: u32,
+    pub cipher: Vec<u8>,
+    pub app_data: Vec<ExpirableBlob>,
+}
+
+/// The witness of the logic wrapper circuit, which recursively verifies a
+/// logic proof given its journal and image ID.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LogicWrapperWitness {
+    pub logic_ref: Digest,
+    pub logic_cm_r: [u8; DEFAULT_BYTES],
+    // The journal of the inner logic proof
+    pub journal: Vec<u8>,
+}
+
+impl LogicWrapperWitness {
+    // The inner proof must be verified against `logic_ref` and `journal`
+    // before calling this
+    pub fn constrain(&self) -> PrivateLogicInstance {
+        let instance: LogicInstance = risc0_zkvm::serde::from_slice(&self.journal).unwrap();
+        PrivateLogicInstance {
+            logic_cm: logic_commitment(&self.logic_ref, &self.logic_cm_r),
+            tag: instance.tag,
+            is_consumed: instance.is_consumed,
+            root: instance.root,
+            action_tree_depth: instance.action_tree_depth,
+            cipher: instance.cipher,
+            app_data: instance.app_data,
+        }
+    }
+}
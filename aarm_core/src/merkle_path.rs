@@ -1,7 +1,11 @@
-use crate::constants::PADDING_LEAVE;
-use risc0_zkvm::sha::{Digest, Impl, Sha256, DIGEST_BYTES};
+use crate::{
+    constants::PADDING_LEAVE,
+    hasher::{HashFunction, Sha256Hasher},
+};
+use risc0_zkvm::sha::Digest;
 use serde::{Deserialize, Serialize};
 use serde_big_array::BigArray;
+use std::marker::PhantomData;
 
 /// A hashable node within a Merkle tree.
 pub trait Hashable: Clone + Copy {
@@ -20,17 +24,7 @@ impl Hashable for Digest {
 
     /// Returns the parent node within the tree of the two given nodes.
     fn combine(lhs: &Self, rhs: &Self) -> Self {
-        let mut bytes = [0u8; 2 * DIGEST_BYTES];
-        let mut offset: usize = 0;
-        // Write the left child
-        bytes[offset..offset + DIGEST_BYTES].clone_from_slice(lhs.as_ref());
-        offset += DIGEST_BYTES;
-        // Write the right child
-        bytes[offset..offset + DIGEST_BYTES].clone_from_slice(rhs.as_ref());
-        offset += DIGEST_BYTES;
-        assert_eq!(offset, 2 * DIGEST_BYTES);
-        // Now produce the hash
-        *Impl::hash_bytes(&bytes)
+        Sha256Hasher::combine(lhs, rhs)
     }
 }
 
@@ -47,13 +41,23 @@ impl<const TREE_DEPTH: usize> MerklePath<TREE_DEPTH> {
         MerklePath { auth_path }
     }
 
+    /// The depth of the tree, i.e. the number of siblings on the path.
+    pub const fn depth(&self) -> usize {
+        TREE_DEPTH
+    }
+
     /// Returns the root of the tree corresponding to this path applied to `leaf`.
     pub fn root(&self, leaf: Digest) -> Digest {
+        self.root_with_hasher::<Sha256Hasher>(leaf)
+    }
+
+    /// Returns the root of the tree hashed with `H`.
+    pub fn root_with_hasher<H: HashFunction>(&self, leaf: Digest) -> Digest {
         self.auth_path
             .iter()
             .fold(leaf, |root, (p, leaf_is_on_right)| match leaf_is_on_right {
-                false => Digest::combine(&root, p),
-                true => Digest::combine(p, &root),
+                false => H::combine(&root, p),
+                true => H::combine(p, &root),
             })
     }
 }
@@ -65,3 +69,100 @@ impl<const TREE_DEPTH: usize> Default for MerklePath<TREE_DEPTH> {
         }
     }
 }
+
+/// A proof of membership of several leaves in the same tree, where the
+/// siblings shared between their paths are only included once. Nodes are
+/// hashed with `H`, as in `MerklePath::root_with_hasher`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleMultiProof<const TREE_DEPTH: usize, H: HashFunction = Sha256Hasher> {
+    // The positions of the leaves, in the order the leaves are given to `root`
+    indices: Vec<usize>,
+    // The siblings that can't be computed from the leaves, level by level and
+    // by increasing position
+    siblings: Vec<Digest>,
+    #[serde(skip)]
+    hasher: PhantomData<H>,
+}
+
+impl<const TREE_DEPTH: usize, H: HashFunction> MerkleMultiProof<TREE_DEPTH, H> {
+    pub fn from_parts(indices: Vec<usize>, siblings: Vec<Digest>) -> Self {
+        MerkleMultiProof {
+            indices,
+            siblings,
+            hasher: PhantomData,
+        }
+    }
+
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// Returns the root of the tree corresponding to this proof applied to
+    /// `leaves`, or `None` if the proof doesn't match the leaves.
+    pub fn root(&self, leaves: &[Digest]) -> Option<Digest> {
+        if leaves.is_empty() || leaves.len() != self.indices.len() {
+            return None;
+        }
+        let mut nodes: Vec<(usize, Digest)> = self
+            .indices
+            .iter()
+            .copied()
+            .zip(leaves.iter().copied())
+            .collect();
+        nodes.sort_by_key(|(index, _)| *index);
+        if nodes.windows(2).any(|pair| pair[0].0 == pair[1].0) || nodes.last()?.0 >= 1 << TREE_DEPTH
+        {
+            return None;
+        }
+
+        let mut siblings = self.siblings.iter();
+        for _ in 0..TREE_DEPTH {
+            let mut parents = Vec::with_capacity(nodes.len());
+            let mut i = 0;
+            while i < nodes.len() {
+                let (position, node) = nodes[i];
+                let parent = if position % 2 == 0 {
+                    // The right sibling is either the next node or in the proof
+                    match nodes.get(i + 1) {
+                        Some((next, right)) if *next == position + 1 => {
+                            i += 1;
+                            H::combine(&node, right)
+                        }
+                        _ => H::combine(&node, siblings.next()?),
+                    }
+                } else {
+                    H::combine(siblings.next()?, &node)
+                };
+                parents.push((position / 2, parent));
+                i += 1;
+            }
+            nodes = parents;
+        }
+
+        // All the siblings must have been used
+        if siblings.next().is_some() {
+            return None;
+        }
+        Some(nodes[0].1)
+    }
+}
+
+#[test]
+fn test_multiproof_hasher() {
+    use crate::hasher::Keccak256Hasher;
+
+    let leaves = [Digest::from([1u32; 8]), Digest::from([2u32; 8])];
+    let root = Keccak256Hasher::combine(&leaves[0], &leaves[1]);
+
+    let both = MerkleMultiProof::<1, Keccak256Hasher>::from_parts(vec![1, 0], vec![]);
+    assert_eq!(both.root(&[leaves[1], leaves[0]]), Some(root));
+    let single = MerkleMultiProof::<1, Keccak256Hasher>::from_parts(vec![0], vec![leaves[1]]);
+    assert_eq!(single.root(&[leaves[0]]), Some(root));
+
+    // The same proof hashed with SHA-256 gives another root
+    let sha_single = MerkleMultiProof::<1>::from_parts(vec![0], vec![leaves[1]]);
+    assert_eq!(
+        sha_single.root(&[leaves[0]]),
+        Some(Sha256Hasher::combine(&leaves[0], &leaves[1]))
+    );
+}
@@ -2,6 +2,7 @@ use crate::constants::{
     DEFAULT_BYTES, DST, PRF_EXPAND_PERSONALIZATION, PRF_EXPAND_PERSONALIZATION_LEN, PRF_EXPAND_PSI,
     PRF_EXPAND_RCM, QUANTITY_BYTES, RESOURCE_BYTES,
 };
+use crate::hasher::{HashFunction, Sha256Hasher};
 use crate::nullifier_key::{NullifierKey, NullifierKeyCommitment};
 use k256::{
     elliptic_curve::hash2curve::{ExpandMsgXmd, GroupDigest},
@@ -115,6 +116,10 @@ impl Resource {
 
     // Compute the commitment to the resource
     pub fn commitment(&self) -> Digest {
+        self.commitment_with_hasher::<Sha256Hasher>()
+    }
+
+    pub fn commitment_with_hasher<H: HashFunction>(&self) -> Digest {
         // Concatenate all the components of this resource
         let mut bytes = [0u8; RESOURCE_BYTES];
         let mut offset: usize = 0;
@@ -145,7 +150,7 @@ impl Resource {
         offset += DEFAULT_BYTES;
         assert_eq!(offset, RESOURCE_BYTES);
         // Now produce the hash
-        *Impl::hash_bytes(&bytes)
+        H::hash_bytes(&bytes)
     }
 
     // Compute the nullifier of the resource
@@ -155,6 +160,14 @@ impl Resource {
     }
 
     pub fn nullifier_from_commitment(&self, nf_key: &NullifierKey, cm: &Digest) -> Option<Digest> {
+        self.nullifier_from_commitment_with_hasher::<Sha256Hasher>(nf_key, cm)
+    }
+
+    pub fn nullifier_from_commitment_with_hasher<H: HashFunction>(
+        &self,
+        nf_key: &NullifierKey,
+        cm: &Digest,
+    ) -> Option<Digest> {
         // Make sure that the nullifier public key corresponds to the secret key
         if self.nk_commitment == nf_key.commit() {
             let mut bytes = [0u8; 4 * DIGEST_BYTES];
@@ -174,7 +187,7 @@ impl Resource {
 
             assert_eq!(offset, 4 * DIGEST_BYTES);
 
-            Some(*Impl::hash_bytes(&bytes))
+            Some(H::hash_bytes(&bytes))
         } else {
             None
         }
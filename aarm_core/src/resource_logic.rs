@@ -1,6 +1,7 @@
 use crate::{
-    action_tree::ACTION_TREE_DEPTH, logic_instance::ExpirableBlob, logic_instance::LogicInstance,
-    merkle_path::MerklePath, nullifier_key::NullifierKey, resource::Resource,
+    action_tree::ACTION_TREE_DEPTH as DEFAULT_ACTION_TREE_DEPTH, logic_instance::ExpirableBlob,
+    logic_instance::LogicInstance, merkle_path::MerklePath, nullifier_key::NullifierKey,
+    resource::Resource,
 };
 use serde::{Deserialize, Serialize};
 
@@ -16,14 +17,14 @@ pub trait LogicCircuit: Default + Clone + Serialize + for<'de> Deserialize<'de>
 }
 
 #[derive(Clone, Default, Serialize, Deserialize)]
-pub struct TrivialLogicWitness {
+pub struct TrivialLogicWitness<const ACTION_TREE_DEPTH: usize = DEFAULT_ACTION_TREE_DEPTH> {
     pub resource: Resource,
     pub receive_existence_path: MerklePath<ACTION_TREE_DEPTH>,
     pub is_consumed: bool,
     pub nf_key: NullifierKey,
 }
 
-impl LogicCircuit for TrivialLogicWitness {
+impl<const ACTION_TREE_DEPTH: usize> LogicCircuit for TrivialLogicWitness<ACTION_TREE_DEPTH> {
     fn constrain(&self) -> LogicInstance {
         // Load the self resource
         let self_cm = self.resource.commitment();
@@ -44,13 +45,14 @@ impl LogicCircuit for TrivialLogicWitness {
             tag,
             is_consumed: self.is_consumed, // It can be either consumed or created to reduce padding resources
             root,
+            action_tree_depth: self.receive_existence_path.depth() as u32,
             cipher: vec![],
             app_data: vec![],
         }
     }
 }
 
-impl TrivialLogicWitness {
+impl<const ACTION_TREE_DEPTH: usize> TrivialLogicWitness<ACTION_TREE_DEPTH> {
     pub fn new(
         resource: Resource,
         receive_existence_path: MerklePath<ACTION_TREE_DEPTH>,
@@ -85,6 +87,7 @@ impl TrivialLogicWitness {
             tag,
             is_consumed: self.is_consumed, // It can be either consumed or created to reduce padding resources
             root,
+            action_tree_depth: self.receive_existence_path.depth() as u32,
             cipher: vec![63, 127, 191, 255], // some dummy cipher for testing
             app_data: vec![
                 ExpirableBlob {
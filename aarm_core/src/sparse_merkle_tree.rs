@@ -0,0 +1,228 @@
+use crate::merkle_path::Hashable;
+use risc0_zkvm::sha::{Digest, DIGEST_BYTES};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+/// The depth of the sparse Merkle tree: a leaf per possible nullifier.
+pub const SPARSE_TREE_DEPTH: usize = 8 * DIGEST_BYTES;
+
+// An empty subtree hashes to zero at every level, so empty siblings don't
+// need to be hashed or included in proofs
+fn hash_node(lhs: &Digest, rhs: &Digest) -> Digest {
+    if *lhs == Digest::ZERO && *rhs == Digest::ZERO {
+        Digest::ZERO
+    } else {
+        Digest::combine(lhs, rhs)
+    }
+}
+
+// The bit of `key` at `depth`, the most significant bit being at depth 0
+fn key_bit(key: &Digest, depth: usize) -> bool {
+    (key.as_bytes()[depth / 8] >> (7 - depth % 8)) & 1 == 1
+}
+
+// The first `depth` bits of `key`
+fn key_prefix(key: &Digest, depth: usize) -> Digest {
+    let mut bytes: [u8; DIGEST_BYTES] = key.as_bytes().try_into().unwrap();
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        if 8 * i >= depth {
+            *byte = 0;
+        } else if 8 * (i + 1) > depth {
+            *byte &= 0xffu8 << (8 * (i + 1) - depth);
+        }
+    }
+    Digest::from(bytes)
+}
+
+/// A compressed path in the sparse Merkle tree, which proves either
+/// membership or non-membership of a key. It can be verified in a
+/// `LogicCircuit::constrain`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SparseMerkleProof {
+    // Bit `i` is set if the sibling at height `i` is not empty
+    bitmap: [u8; DIGEST_BYTES],
+    // The non-empty siblings, from the leaf up to the root
+    siblings: Vec<Digest>,
+}
+
+impl SparseMerkleProof {
+    /// Returns the root of the tree where the leaf of `key` is `leaf`, or
+    /// `None` if the proof is malformed.
+    pub fn root(&self, key: &Digest, leaf: &Digest) -> Option<Digest> {
+        let mut siblings = self.siblings.iter();
+        let mut node = *leaf;
+        for height in 0..SPARSE_TREE_DEPTH {
+            let sibling = if (self.bitmap[height / 8] >> (height % 8)) & 1 == 1 {
+                *siblings.next()?
+            } else {
+                Digest::ZERO
+            };
+            node = if key_bit(key, SPARSE_TREE_DEPTH - 1 - height) {
+                hash_node(&sibling, &node)
+            } else {
+                hash_node(&node, &sibling)
+            };
+        }
+        if siblings.next().is_some() {
+            return None;
+        }
+        Some(node)
+    }
+
+    /// Checks that `key` is in the tree of `root`.
+    pub fn verify_membership(&self, root: &Digest, key: &Digest) -> bool {
+        self.root(key, key) == Some(*root)
+    }
+
+    /// Checks that `key` is not in the tree of `root`.
+    pub fn verify_non_membership(&self, root: &Digest, key: &Digest) -> bool {
+        self.root(key, &Digest::ZERO) == Some(*root)
+    }
+}
+
+/// A host-side sparse Merkle tree of nullifiers, indexed by node position so
+/// that insertions and proofs only touch a single path.
+///
+/// The leaf of a key is the key itself if it is in the tree and zero
+/// otherwise.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SparseMerkleTree {
+    // The non-empty nodes, by depth and key prefix
+    nodes: HashMap<(usize, Digest), Digest>,
+    keys: BTreeSet<Digest>,
+}
+
+impl SparseMerkleTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn contains(&self, key: &Digest) -> bool {
+        self.keys.contains(key)
+    }
+
+    pub fn root(&self) -> Digest {
+        self.node(0, &Digest::ZERO)
+    }
+
+    /// Inserts `key` and returns `false` if it was already in the tree.
+    pub fn insert(&mut self, key: Digest) -> bool {
+        assert_ne!(key, Digest::ZERO, "The zero key denotes an empty leaf");
+        if !self.keys.insert(key) {
+            return false;
+        }
+
+        let mut node = key;
+        self.nodes.insert((SPARSE_TREE_DEPTH, key), node);
+        for depth in (0..SPARSE_TREE_DEPTH).rev() {
+            let sibling = self.sibling(&key, depth + 1);
+            node = if key_bit(&key, depth) {
+                hash_node(&sibling, &node)
+            } else {
+                hash_node(&node, &sibling)
+            };
+            self.nodes.insert((depth, key_prefix(&key, depth)), node);
+        }
+        true
+    }
+
+    /// Generates the path of `key`, proving membership if the key is in the
+    /// tree and non-membership otherwise.
+    pub fn prove(&self, key: &Digest) -> SparseMerkleProof {
+        let mut proof = SparseMerkleProof::default();
+        for height in 0..SPARSE_TREE_DEPTH {
+            let sibling = self.sibling(key, SPARSE_TREE_DEPTH - height);
+            if sibling != Digest::ZERO {
+                proof.bitmap[height / 8] |= 1 << (height % 8);
+                proof.siblings.push(sibling);
+            }
+        }
+        proof
+    }
+
+    fn node(&self, depth: usize, prefix: &Digest) -> Digest {
+        self.nodes
+            .get(&(depth, *prefix))
+            .copied()
+            .unwrap_or(Digest::ZERO)
+    }
+
+    // The sibling at `depth` of the node on the path of `key`
+    fn sibling(&self, key: &Digest, depth: usize) -> Digest {
+        let mut bytes: [u8; DIGEST_BYTES] = key_prefix(key, depth).as_bytes().try_into().unwrap();
+        let bit = depth - 1;
+        bytes[bit / 8] ^= 1 << (7 - bit % 8);
+        self.node(depth, &Digest::from(bytes))
+    }
+}
+
+impl Serialize for SparseMerkleTree {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.keys.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SparseMerkleTree {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let keys = BTreeSet::<Digest>::deserialize(deserializer)?;
+        let mut tree = SparseMerkleTree::new();
+        for key in keys {
+            if key == Digest::ZERO {
+                return Err(serde::de::Error::custom("Zero key in SparseMerkleTree"));
+            }
+            tree.insert(key);
+        }
+        Ok(tree)
+    }
+}
+
+#[test]
+fn test_sparse_merkle_tree() {
+    let mut tree = SparseMerkleTree::new();
+    let keys: Vec<Digest> = (1..6u32).map(|i| Digest::from([i; 8])).collect();
+    let absent = Digest::from([42u32; 8]);
+    assert_eq!(tree.root(), Digest::ZERO);
+    assert!(tree
+        .prove(&absent)
+        .verify_non_membership(&tree.root(), &absent));
+
+    for key in &keys {
+        assert!(tree.insert(*key));
+    }
+    assert!(!tree.insert(keys[0]));
+    assert_eq!(tree.len(), keys.len());
+
+    let root = tree.root();
+    for key in &keys {
+        let proof = tree.prove(key);
+        assert!(proof.verify_membership(&root, key));
+        assert!(!proof.verify_non_membership(&root, key));
+    }
+    let proof = tree.prove(&absent);
+    assert!(proof.verify_non_membership(&root, &absent));
+    assert!(!proof.verify_membership(&root, &absent));
+
+    // The root only depends on the set of keys
+    let restored: SparseMerkleTree =
+        bincode::deserialize(&bincode::serialize(&tree).unwrap()).unwrap();
+    assert_eq!(restored.root(), root);
+    let mut reversed = SparseMerkleTree::new();
+    for key in keys.iter().rev() {
+        reversed.insert(*key);
+    }
+    assert_eq!(reversed.root(), root);
+}
@@ -0,0 +1,26 @@
+use aarm_core::aggregation::AggregationWitness;
+use risc0_zkvm::guest::env;
+
+// Guest code:
+// This is the portion of the code that will be proven
+pub fn main() {
+    let aggregation_witness: AggregationWitness = env::read();
+
+    // Recursively verify all the proofs of the transaction, which are added
+    // as assumptions by the host
+    for action in &aggregation_witness.actions {
+        for journal in &action.compliance_journals {
+            env::verify(aggregation_witness.compliance_id, journal).unwrap();
+        }
+        for journal in &action.multi_compliance_journals {
+            env::verify(aggregation_witness.multi_compliance_id, journal).unwrap();
+        }
+        for (verifying_key, journal) in &action.logic_journals {
+            env::verify(*verifying_key, journal).unwrap();
+        }
+    }
+
+    let aggregation_instance = aggregation_witness.constrain();
+
+    env::commit(&aggregation_instance);
+}
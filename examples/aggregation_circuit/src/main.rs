@@ -0,0 +1,18 @@
+use aggregation_methods::{AGGREGATION_GUEST_ELF, AGGREGATION_GUEST_ID};
+
+// The aggregation needs all the receipts of a transaction as assumptions, see
+// `aarm::transaction::Transaction::aggregate` for proving.
+pub fn main() {
+    println!("Aggregation Guest ID: {:?}", AGGREGATION_GUEST_ID);
+}
+
+#[ignore]
+#[test]
+fn print_aggregation_elf_id() {
+    // Write the elf binary to a file
+    std::fs::write("../../aarm/elfs/aggregation_elf.bin", AGGREGATION_GUEST_ELF)
+        .expect("Failed to write aggregation guest ELF binary");
+
+    // Print the ID
+    println!("Aggregation Guest ID: {:?}", AGGREGATION_GUEST_ID);
+}
@@ -111,6 +111,7 @@ impl LogicCircuit for KudoResourceLogicWitness {
             tag,
             is_consumed: self.kudo_is_consumed,
             root,
+            action_tree_depth: self.kudo_existence_path.depth() as u32,
             cipher,
             app_data: Vec::new(),
         }
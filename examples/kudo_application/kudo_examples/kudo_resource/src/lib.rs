@@ -3,7 +3,7 @@ use aarm_core::{nullifier_key::NullifierKey, resource::Resource};
 use kudo_core::kudo::Kudo;
 pub const KUDO_LOGIC_ELF: &[u8] = include_bytes!("../../../elfs/kudo-logic.bin");
 pub const KUDO_LOGIC_ID: [u32; 8] = [
-    2180851034, 2718074376, 3239141689, 1351813527, 1622732300, 2436380035, 1838980537, 1125642721,
+    2348869458, 287320885, 4027182231, 1286633819, 1555323255, 3475655992, 1613229896, 3144307145,
 ];
 pub use kudo_resource_core::KudoResourceLogicWitness;
 use risc0_zkvm::sha::Digest;
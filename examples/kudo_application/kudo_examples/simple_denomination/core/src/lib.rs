@@ -94,6 +94,7 @@ impl LogicCircuit for SimpleDenominationWitness {
             tag: denomination_tag,
             is_consumed: self.denomination_is_consumed,
             root,
+            action_tree_depth: self.denomination_existence_path.depth() as u32,
             cipher: Ciphertext::default().inner(), // no cipher needed
             app_data: Vec::new(),                  // no app data needed
         }
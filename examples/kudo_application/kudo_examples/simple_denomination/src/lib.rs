@@ -3,7 +3,7 @@ use aarm_core::{nullifier_key::NullifierKey, resource::Resource};
 pub use denomination_core::SimpleDenominationWitness;
 pub const DENOMINATION_ELF: &[u8] = include_bytes!("../../../elfs/denomination.bin");
 pub const DENOMINATION_ID: [u32; 8] = [
-    752765321, 2072722720, 3155356202, 794211336, 1132666731, 942238722, 2377137829, 2293574572,
+    1143860991, 2598780029, 2777555864, 4118996822, 3810311606, 1737242854, 2330707080, 1907915778,
 ];
 use kudo_core::denomination::Denomination;
 use risc0_zkvm::sha::Digest;
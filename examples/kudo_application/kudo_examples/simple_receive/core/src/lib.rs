@@ -51,6 +51,7 @@ impl LogicCircuit for SimpleReceiveWitness {
             tag,
             is_consumed: self.is_consumed, // It can be either consumed or created to reduce padding resources
             root,
+            action_tree_depth: self.receive_existence_path.depth() as u32,
             cipher: Ciphertext::default().inner(), // no cipher needed
             app_data: Vec::new(),                  // no app data needed
         }
@@ -4,7 +4,7 @@ use kudo_core::receive::Receive;
 pub use receive_core::SimpleReceiveWitness;
 pub const RECEIVE_ELF: &[u8] = include_bytes!("../../../elfs/receive.bin");
 pub const RECEIVE_ID: [u32; 8] = [
-    3310211775, 589008629, 4235198033, 3102914864, 1797110209, 280090754, 4131591913, 700179597,
+    1622615307, 271157528, 1093448467, 2571070366, 3468518491, 782511207, 2960962538, 23720643,
 ];
 use risc0_zkvm::sha::Digest;
 use serde::{Deserialize, Serialize};
@@ -1,14 +1,11 @@
 use aarm::{
-    action::Action,
-    constants::COMPLIANCE_GUEST_ELF,
+    action_builder::ActionBuilder,
+    error::ProvingError,
     logic_proof::LogicProver,
     transaction::{Delta, Transaction},
-    utils::groth16_prove,
-};
-use aarm_core::{
-    compliance::ComplianceWitness, constants::COMMITMENT_TREE_DEPTH, delta_proof::DeltaWitness,
-    merkle_path::MerklePath,
+    utils::ProofMode,
 };
+use aarm_core::{constants::COMMITMENT_TREE_DEPTH, merkle_path::MerklePath};
 use kudo_core::{denomination::Denomination, kudo::Kudo};
 
 #[derive(Clone)]
@@ -29,75 +26,40 @@ where
     K: Kudo + LogicProver,
     D: Denomination + LogicProver,
 {
-    pub fn create_tx(&self) -> Transaction {
-        // Create the action
-        let (action, delta_witness) = {
-            // Generate compliance units
-            // Compliance unit 1: the ephemeral_kudo_resource and the issued_kudo_resource
-
-            println!("Generating compliance unit 1");
-            let (compliance_unit_1, delta_witness_1) = {
-                let compliance_witness: ComplianceWitness<COMMITMENT_TREE_DEPTH> =
-                    ComplianceWitness::from_resources_with_path(
-                        self.burned_kudo.resource(),
-                        self.burned_kudo.nf_key(),
-                        self.burned_kudo_path,
-                        self.burned_denomination.resource(),
-                    );
-
-                (
-                    groth16_prove(&compliance_witness, COMPLIANCE_GUEST_ELF),
-                    compliance_witness.rcv,
-                )
-            };
-
-            // Compliance unit 2: the issued_receive_resource and the issued_denomination_resource
-            println!("Generating compliance unit 2");
-            let (compliance_unit_2, delta_witness_2) = {
-                let compliance_witness: ComplianceWitness<COMMITMENT_TREE_DEPTH> =
-                    ComplianceWitness::from_resources(
-                        self.ephemeral_denomination.resource(),
-                        self.ephemeral_denomination.nf_key(),
-                        self.ephemeral_kudo.resource(),
-                    );
-
-                (
-                    groth16_prove(&compliance_witness, COMPLIANCE_GUEST_ELF),
-                    compliance_witness.rcv,
-                )
-            };
-
-            // Generate logic proofs
-            println!("Generating the burned kudo logic proof");
-            let burned_kudo_proof = self.burned_kudo.prove();
-
-            println!(
-                "Generating the denomination logic proof corresponding to the burned kudo resource"
-            );
-            let burned_denomination_proof = self.burned_denomination.prove();
-
-            println!("Generating the ephemeral kudo logic proof");
-            let ephemeral_kudo_proof = self.ephemeral_kudo.prove();
+    pub fn create_tx(&self, mode: ProofMode) -> Result<Transaction, ProvingError> {
+        // The logic witnesses already hold their paths in the action tree,
+        // so the resources are added in the order of that tree and the paths
+        // given by the builder are unused
+        let mut builder = ActionBuilder::new();
 
-            println!("Generating the denomination logic proof corresponding to the ephemeral kudo resource");
-            let ephemeral_denomination_proof = self.ephemeral_denomination.prove();
+        // Compliance unit 1: the burned kudo resource and its denomination resource
+        builder.add_consumed(
+            self.burned_kudo.resource(),
+            self.burned_kudo.nf_key(),
+            self.burned_kudo_path,
+            Box::new(|_, _, mode| self.burned_kudo.prove(mode)),
+        );
+        builder.add_created(
+            self.burned_denomination.resource(),
+            Box::new(|_, _, mode| self.burned_denomination.prove(mode)),
+        );
 
-            (
-                Action::new(
-                    vec![compliance_unit_1, compliance_unit_2],
-                    vec![
-                        burned_kudo_proof,
-                        burned_denomination_proof,
-                        ephemeral_kudo_proof,
-                        ephemeral_denomination_proof,
-                    ],
-                    vec![],
-                ),
-                DeltaWitness::from_scalars(&[delta_witness_1, delta_witness_2]),
-            )
-        };
+        // Compliance unit 2: the ephemeral denomination resource and the ephemeral kudo resource
+        builder.add_consumed(
+            self.ephemeral_denomination.resource(),
+            self.ephemeral_denomination.nf_key(),
+            MerklePath::default(),
+            Box::new(|_, _, mode| self.ephemeral_denomination.prove(mode)),
+        );
+        builder.add_created(
+            self.ephemeral_kudo.resource(),
+            Box::new(|_, _, mode| self.ephemeral_kudo.prove(mode)),
+        );
 
-        // Create the transaction
-        Transaction::new(vec![action], Delta::Witness(delta_witness))
+        let (action, delta_witness) = builder.build(mode)?;
+        Ok(Transaction::new(
+            vec![action],
+            Delta::Witness(delta_witness),
+        ))
     }
 }
@@ -1,14 +1,11 @@
 use aarm::{
-    action::Action,
-    constants::COMPLIANCE_GUEST_ELF,
+    action_builder::ActionBuilder,
+    error::ProvingError,
     logic_proof::{LogicProver, PaddingResourceLogic},
     transaction::{Delta, Transaction},
-    utils::groth16_prove,
-};
-use aarm_core::{
-    compliance::ComplianceWitness, constants::COMMITMENT_TREE_DEPTH, delta_proof::DeltaWitness,
-    merkle_path::MerklePath,
+    utils::ProofMode,
 };
+use aarm_core::{constants::COMMITMENT_TREE_DEPTH, merkle_path::MerklePath};
 use kudo_core::{denomination::Denomination, kudo::Kudo, receive::Receive};
 
 // TODO: SwapInstance seems simillar to TransferWitness, consider abstracting and
@@ -39,101 +36,52 @@ where
     D2: Denomination + LogicProver,
     R: Receive + LogicProver,
 {
-    pub fn create_tx(&self) -> Transaction {
-        // Create the action
-        let (action, delta_witness) = {
-            // Generate compliance units Compliance unit 1: the consumed kudo
-            // resource and the consumed denomination resource
-
-            println!("Generating compliance unit 1");
-            let (compliance_unit_1, delta_witness_1) = {
-                let compliance_witness: ComplianceWitness<COMMITMENT_TREE_DEPTH> =
-                    ComplianceWitness::from_resources_with_path(
-                        self.consumed_kudo.resource(),
-                        self.consumed_kudo.nf_key(),
-                        self.consumed_kudo_path,
-                        self.consumed_denomination.resource(),
-                    );
-
-                (
-                    groth16_prove(&compliance_witness, COMPLIANCE_GUEST_ELF),
-                    compliance_witness.rcv,
-                )
-            };
-
-            // Compliance unit 2: the created kudo resource and the created
-            // denomination resource
-            println!("Generating compliance unit 2");
-            let (compliance_unit_2, delta_witness_2) = {
-                let compliance_witness: ComplianceWitness<COMMITMENT_TREE_DEPTH> =
-                    ComplianceWitness::from_resources(
-                        self.created_denomination.resource(),
-                        self.created_denomination.nf_key(),
-                        self.created_kudo.resource(),
-                    );
-
-                (
-                    groth16_prove(&compliance_witness, COMPLIANCE_GUEST_ELF),
-                    compliance_witness.rcv,
-                )
-            };
-
-            // Compliance unit 3: the receive loigc resource and the padding
-            // resource
-            println!("Generating compliance unit 3");
-            let (compliance_unit_3, delta_witness_3) = {
-                let compliance_witness: ComplianceWitness<COMMITMENT_TREE_DEPTH> =
-                    ComplianceWitness::from_resources(
-                        self.padding_resource_logic.witness().resource,
-                        self.padding_resource_logic.witness().nf_key,
-                        self.created_receive.resource(),
-                    );
-
-                (
-                    groth16_prove(&compliance_witness, COMPLIANCE_GUEST_ELF),
-                    compliance_witness.rcv,
-                )
-            };
-
-            // Generate logic proofs
-            println!("Generating the consumed kudo logic proof");
-            let consumed_kudo_proof = self.consumed_kudo.prove();
-
-            println!(
-                "Generating the denomination logic proof corresponding to the consumed kudo resource"
-            );
-            let consumed_denomination_proof = self.consumed_denomination.prove();
-
-            println!("Generating the created kudo logic proof");
-            let created_kudo_proof = self.created_kudo.prove();
-
-            println!("Generating the denomination logic proof corresponding to the created kudo resource");
-            let created_denomination_proof = self.created_denomination.prove();
-
-            println!("Generating the padding resource logic proof");
-            let padding_resource_proof = self.padding_resource_logic.prove();
-
-            println!("Generating the receive logic proof");
-            let receive_logic_proof = self.created_receive.prove();
-
-            (
-                Action::new(
-                    vec![compliance_unit_1, compliance_unit_2, compliance_unit_3],
-                    vec![
-                        consumed_kudo_proof,
-                        consumed_denomination_proof,
-                        created_kudo_proof,
-                        created_denomination_proof,
-                        padding_resource_proof,
-                        receive_logic_proof,
-                    ],
-                    vec![],
-                ),
-                DeltaWitness::from_scalars(&[delta_witness_1, delta_witness_2, delta_witness_3]),
-            )
-        };
-
-        // Create the transaction
-        Transaction::new(vec![action], Delta::Witness(delta_witness))
+    pub fn create_tx(&self, mode: ProofMode) -> Result<Transaction, ProvingError> {
+        // The logic witnesses already hold their paths in the action tree,
+        // so the resources are added in the order of that tree and the paths
+        // given by the builder are unused
+        let mut builder = ActionBuilder::new();
+
+        // Compliance unit 1: the consumed kudo resource and the consumed denomination resource
+        builder.add_consumed(
+            self.consumed_kudo.resource(),
+            self.consumed_kudo.nf_key(),
+            self.consumed_kudo_path,
+            Box::new(|_, _, mode| self.consumed_kudo.prove(mode)),
+        );
+        builder.add_created(
+            self.consumed_denomination.resource(),
+            Box::new(|_, _, mode| self.consumed_denomination.prove(mode)),
+        );
+
+        // Compliance unit 2: the created denomination resource and the created kudo resource
+        builder.add_consumed(
+            self.created_denomination.resource(),
+            self.created_denomination.nf_key(),
+            MerklePath::default(),
+            Box::new(|_, _, mode| self.created_denomination.prove(mode)),
+        );
+        builder.add_created(
+            self.created_kudo.resource(),
+            Box::new(|_, _, mode| self.created_kudo.prove(mode)),
+        );
+
+        // Compliance unit 3: a padding resource and the created receive resource
+        builder.add_consumed(
+            self.padding_resource_logic.witness().resource,
+            self.padding_resource_logic.witness().nf_key,
+            MerklePath::default(),
+            Box::new(|_, _, mode| self.padding_resource_logic.prove(mode)),
+        );
+        builder.add_created(
+            self.created_receive.resource(),
+            Box::new(|_, _, mode| self.created_receive.prove(mode)),
+        );
+
+        let (action, delta_witness) = builder.build(mode)?;
+        Ok(Transaction::new(
+            vec![action],
+            Delta::Witness(delta_witness),
+        ))
     }
 }
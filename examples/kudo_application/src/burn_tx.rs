@@ -68,7 +68,8 @@ pub fn build_burn_tx(
         burned_denomination_resource_cm,
         ephemeral_denomination_resource_nf,
         ephemeral_kudo_resource_cm,
-    ]);
+    ])
+    .unwrap();
     let root = action_tree.root();
 
     // Generate paths
@@ -146,6 +147,9 @@ pub fn build_burn_tx(
 
 #[test]
 fn generate_a_burn_tx() {
+    use aarm::utils::ProofMode;
+    use aarm_core::commitment_tree::CommitmentTree;
+
     let issuer_sk = AuthorizationSigningKey::new();
     let issuer = AuthorizationVerifyingKey::from_signing_key(&issuer_sk);
     // TODO: fix the kudo_logic
@@ -159,16 +163,19 @@ fn generate_a_burn_tx() {
     let kudo_resource =
         Resource::create(kudo_logic, kudo_lable, 100, kudo_value, false, kudo_nk_cm);
 
+    let mut commitment_tree = CommitmentTree::<COMMITMENT_TREE_DEPTH>::new();
+    let kudo_position = commitment_tree.append(kudo_resource.commitment()).unwrap();
+
     let burn_witness = build_burn_tx(
         &issuer_sk,
         &owner_sk,
         &kudo_resource,
         &kudo_nf_key,
-        MerklePath::<COMMITMENT_TREE_DEPTH>::default(), // It should be a real path
+        commitment_tree.path(kudo_position).unwrap(),
     );
 
-    let mut tx = burn_witness.create_tx();
-    tx.generate_delta_proof();
+    let mut tx = burn_witness.create_tx(ProofMode::Groth16).unwrap();
+    tx.generate_delta_proof().unwrap();
 
-    assert!(tx.verify());
+    assert!(tx.verify().is_ok());
 }
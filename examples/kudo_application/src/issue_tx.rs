@@ -92,7 +92,8 @@ pub fn build_issue_tx(
         issued_denomination_resource_cm,
         padding_resource_nf,
         ephemeral_denomination_resource_cm,
-    ]);
+    ])
+    .unwrap();
     let root = action_tree.root();
 
     // Generate paths
@@ -197,6 +198,7 @@ pub fn build_issue_tx(
 
 #[test]
 fn generate_an_issue_tx() {
+    use aarm::utils::ProofMode;
     use kudo_core::utils::generate_receive_signature;
 
     let (receiver_pk, receiver_signature) = {
@@ -214,8 +216,8 @@ fn generate_an_issue_tx() {
         &NullifierKeyCommitment::default(),
     );
 
-    let mut tx = issue_witness.create_tx();
-    tx.generate_delta_proof();
+    let mut tx = issue_witness.create_tx(ProofMode::Groth16).unwrap();
+    tx.generate_delta_proof().unwrap();
 
-    assert!(tx.verify());
+    assert!(tx.verify().is_ok());
 }
@@ -1,4 +1,6 @@
 pub mod burn_tx;
 pub mod issue_tx;
+#[cfg(test)]
+mod lifecycle;
 pub mod swap_tx;
 pub mod transfer_tx;
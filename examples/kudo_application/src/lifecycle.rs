@@ -0,0 +1,145 @@
+use crate::{
+    burn_tx::build_burn_tx, issue_tx::build_issue_tx, swap_tx::build_swap_tx,
+    transfer_tx::build_transfer_tx,
+};
+use aarm::{
+    error::LedgerError, ledger::Ledger, logic_proof::LogicProver, transaction::Transaction,
+    utils::ProofMode,
+};
+use aarm_core::{
+    authorization::{AuthorizationSigningKey, AuthorizationVerifyingKey},
+    nullifier_key::NullifierKey,
+};
+use kudo_core::{kudo::Kudo, utils::generate_receive_signature};
+use simple_receive::SimpleReceiveLogic;
+
+fn prove_tx(mut tx: Transaction) -> Transaction {
+    tx.generate_delta_proof().unwrap();
+    tx
+}
+
+#[test]
+fn kudo_lifecycle_on_ledger() {
+    let mut ledger = Ledger::new();
+    let receive_logic = SimpleReceiveLogic::verifying_key();
+
+    let issuer_a_sk = AuthorizationSigningKey::new();
+    let issuer_a = AuthorizationVerifyingKey::from_signing_key(&issuer_a_sk);
+    let issuer_b_sk = AuthorizationSigningKey::new();
+    let issuer_b = AuthorizationVerifyingKey::from_signing_key(&issuer_b_sk);
+    let (issuer_b_nf_key, issuer_b_nk_cm) = NullifierKey::random_pair();
+
+    let alice_sk = AuthorizationSigningKey::new();
+    let alice_pk = AuthorizationVerifyingKey::from_signing_key(&alice_sk);
+    let (alice_nf_key, alice_nk_cm) = NullifierKey::random_pair();
+    let bob_sk = AuthorizationSigningKey::new();
+    let bob_pk = AuthorizationVerifyingKey::from_signing_key(&bob_sk);
+    let (bob_nf_key, bob_nk_cm) = NullifierKey::random_pair();
+    let carol_sk = AuthorizationSigningKey::new();
+    let carol_pk = AuthorizationVerifyingKey::from_signing_key(&carol_sk);
+    let (carol_nf_key, carol_nk_cm) = NullifierKey::random_pair();
+
+    // Issue 100 A kudos to alice and 200 B kudos to carol
+    let alice_issue = build_issue_tx(
+        &issuer_a_sk,
+        100,
+        &alice_pk,
+        &generate_receive_signature(&receive_logic, &alice_sk),
+        &alice_nk_cm,
+    );
+    let alice_kudo = alice_issue.issue_kudo.resource();
+    let tx = prove_tx(alice_issue.create_tx(ProofMode::Dev).unwrap());
+    ledger.apply(&tx).unwrap();
+
+    let carol_issue = build_issue_tx(
+        &issuer_b_sk,
+        200,
+        &carol_pk,
+        &generate_receive_signature(&receive_logic, &carol_sk),
+        &carol_nk_cm,
+    );
+    let carol_kudo = carol_issue.issue_kudo.resource();
+    let tx = prove_tx(carol_issue.create_tx(ProofMode::Dev).unwrap());
+    ledger.apply(&tx).unwrap();
+
+    // Alice transfers her kudos to bob
+    let transfer = build_transfer_tx(
+        &issuer_a,
+        &alice_sk,
+        &alice_kudo,
+        &alice_nf_key,
+        ledger.commitment_path(&alice_kudo.commitment()).unwrap(),
+        &bob_pk,
+        &generate_receive_signature(&receive_logic, &bob_sk),
+        &bob_nk_cm,
+    );
+    let bob_kudo = transfer.created_kudo.resource();
+    let tx = prove_tx(transfer.create_tx(ProofMode::Dev).unwrap());
+    ledger.apply(&tx).unwrap();
+    assert!(ledger.contains_commitment(&bob_kudo.commitment()));
+
+    // Bob swaps his A kudos against carol's B kudos
+    let bob_swap = build_swap_tx(
+        &issuer_a,
+        &bob_sk,
+        &bob_kudo,
+        &bob_nf_key,
+        ledger.commitment_path(&bob_kudo.commitment()).unwrap(),
+        &issuer_b,
+        200,
+    );
+    let bob_swapped_kudo = bob_swap.created_kudo.resource();
+    let carol_swap = build_swap_tx(
+        &issuer_b,
+        &carol_sk,
+        &carol_kudo,
+        &carol_nf_key,
+        ledger.commitment_path(&carol_kudo.commitment()).unwrap(),
+        &issuer_a,
+        100,
+    );
+    let tx = prove_tx(
+        Transaction::compose(
+            bob_swap.create_tx(ProofMode::Dev).unwrap(),
+            carol_swap.create_tx(ProofMode::Dev).unwrap(),
+        )
+        .unwrap(),
+    );
+    ledger.apply(&tx).unwrap();
+
+    // Only the issuer can burn kudos, so bob returns the B kudos he received
+    let transfer = build_transfer_tx(
+        &issuer_b,
+        &bob_sk,
+        &bob_swapped_kudo,
+        &bob_nf_key,
+        ledger
+            .commitment_path(&bob_swapped_kudo.commitment())
+            .unwrap(),
+        &issuer_b,
+        &generate_receive_signature(&receive_logic, &issuer_b_sk),
+        &issuer_b_nk_cm,
+    );
+    let returned_kudo = transfer.created_kudo.resource();
+    let tx = prove_tx(transfer.create_tx(ProofMode::Dev).unwrap());
+    ledger.apply(&tx).unwrap();
+    assert!(ledger.is_nullifier_spent(&bob_swapped_kudo.nullifier(&bob_nf_key).unwrap()));
+
+    // The issuer burns the returned kudos
+    let burn = build_burn_tx(
+        &issuer_b_sk,
+        &issuer_b_sk,
+        &returned_kudo,
+        &issuer_b_nf_key,
+        ledger.commitment_path(&returned_kudo.commitment()).unwrap(),
+    );
+    let tx = prove_tx(burn.create_tx(ProofMode::Dev).unwrap());
+    ledger.apply(&tx).unwrap();
+    assert!(ledger.is_nullifier_spent(&returned_kudo.nullifier(&issuer_b_nf_key).unwrap()));
+
+    // The burned kudos can't be spent again
+    assert!(matches!(
+        ledger.apply(&tx),
+        Err(LedgerError::DoubleSpend(_))
+    ));
+}
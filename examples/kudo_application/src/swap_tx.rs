@@ -100,7 +100,8 @@ pub fn build_swap_tx(
         created_kudo_value_cm,
         padding_resource_nf,
         receive_resource_cm,
-    ]);
+    ])
+    .unwrap();
     let root = action_tree.root();
 
     // Generate paths
@@ -207,7 +208,8 @@ pub fn build_swap_tx(
 
 #[test]
 fn generate_a_swap_tx() {
-    use aarm::transaction::Transaction;
+    use aarm::{transaction::Transaction, utils::ProofMode};
+    use aarm_core::commitment_tree::CommitmentTree;
 
     let kudo_logic = KudoResourceLogic::verifying_key();
     // The issuer determines the kind of kudo
@@ -238,17 +240,22 @@ fn generate_a_swap_tx() {
     let alice_created_kudo_lable = compute_kudo_label(&kudo_logic, &alice_created_issuer);
     let alice_created_kudo_quantity = 200;
 
+    let mut commitment_tree = CommitmentTree::<COMMITMENT_TREE_DEPTH>::new();
+    let alice_kudo_position = commitment_tree
+        .append(alice_consumed_kudo_resource.commitment())
+        .unwrap();
+
     let alice_swap_witness = build_swap_tx(
         &alice_consumed_issuer,
         &alice_sk,
         &alice_consumed_kudo_resource,
         &alice_kudo_nf_key,
-        MerklePath::<COMMITMENT_TREE_DEPTH>::default(), // It should be a real path
+        commitment_tree.path(alice_kudo_position).unwrap(),
         &alice_created_issuer,
         alice_created_kudo_quantity,
     );
 
-    let alice_tx = alice_swap_witness.create_tx();
+    let alice_tx = alice_swap_witness.create_tx(ProofMode::Groth16).unwrap();
 
     let bob_sk = AuthorizationSigningKey::new();
     let bob_pk = AuthorizationVerifyingKey::from_signing_key(&bob_sk);
@@ -265,18 +272,21 @@ fn generate_a_swap_tx() {
     let bob_consumed_issuer = alice_created_issuer;
     let bob_created_issuer = alice_consumed_issuer;
     let bob_created_kudo_quantity = alice_consumed_kudo_quantity;
+    let bob_kudo_position = commitment_tree
+        .append(bob_consumed_kudo_resource.commitment())
+        .unwrap();
     let bob_swap_witness = build_swap_tx(
         &bob_consumed_issuer,
         &bob_sk,
         &bob_consumed_kudo_resource,
         &bob_kudo_nf_key,
-        MerklePath::<COMMITMENT_TREE_DEPTH>::default(), // It should be a real path
+        commitment_tree.path(bob_kudo_position).unwrap(),
         &bob_created_issuer,
         bob_created_kudo_quantity,
     );
-    let bob_tx = bob_swap_witness.create_tx();
+    let bob_tx = bob_swap_witness.create_tx(ProofMode::Groth16).unwrap();
 
-    let mut tx = Transaction::compose(alice_tx, bob_tx);
-    tx.generate_delta_proof();
-    assert!(tx.verify());
+    let mut tx = Transaction::compose(alice_tx, bob_tx).unwrap();
+    tx.generate_delta_proof().unwrap();
+    assert!(tx.verify().is_ok());
 }
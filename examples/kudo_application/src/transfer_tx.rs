@@ -95,7 +95,8 @@ pub fn build_transfer_tx(
         created_kudo_value_cm,
         padding_resource_nf,
         receive_resource_cm,
-    ]);
+    ])
+    .unwrap();
     let root = action_tree.root();
 
     // Generate paths
@@ -200,6 +201,8 @@ pub fn build_transfer_tx(
 
 #[test]
 fn generate_a_transfer_tx() {
+    use aarm::utils::ProofMode;
+    use aarm_core::commitment_tree::CommitmentTree;
     use kudo_core::utils::generate_receive_signature;
 
     let kudo_logic = KudoResourceLogic::verifying_key();
@@ -222,19 +225,24 @@ fn generate_a_transfer_tx() {
     let consumed_kudo_resource =
         Resource::create(kudo_logic, kudo_lable, 100, kudo_value, false, kudo_nk_cm);
 
+    let mut commitment_tree = CommitmentTree::<COMMITMENT_TREE_DEPTH>::new();
+    let kudo_position = commitment_tree
+        .append(consumed_kudo_resource.commitment())
+        .unwrap();
+
     let transfer_witness = build_transfer_tx(
         &issuer,
         &owner_sk,
         &consumed_kudo_resource,
         &kudo_nf_key,
-        MerklePath::<COMMITMENT_TREE_DEPTH>::default(), // It should be a real path
+        commitment_tree.path(kudo_position).unwrap(),
         &receiver_pk,
         &receiver_signature,
         &receiver_nk_commitment,
     );
 
-    let mut tx = transfer_witness.create_tx();
-    tx.generate_delta_proof();
+    let mut tx = transfer_witness.create_tx(ProofMode::Groth16).unwrap();
+    tx.generate_delta_proof().unwrap();
 
-    assert!(tx.verify());
+    assert!(tx.verify().is_ok());
 }
@@ -0,0 +1,16 @@
+use aarm_core::logic_instance::LogicWrapperWitness;
+use risc0_zkvm::guest::env;
+
+// Guest code:
+// This is the portion of the code that will be proven
+pub fn main() {
+    let wrapper_witness: LogicWrapperWitness = env::read();
+
+    // Recursively verify the logic proof, which is added as an assumption
+    // by the host
+    env::verify(wrapper_witness.logic_ref, &wrapper_witness.journal).unwrap();
+
+    let logic_instance = wrapper_witness.constrain();
+
+    env::commit(&logic_instance);
+}
@@ -0,0 +1,21 @@
+use logic_wrapper_methods::{LOGIC_WRAPPER_GUEST_ELF, LOGIC_WRAPPER_GUEST_ID};
+
+// The wrapper needs an inner logic receipt as an assumption, see
+// `aarm::private_action::PrivateLogicProof::wrap` for proving.
+pub fn main() {
+    println!("Logic Wrapper Guest ID: {:?}", LOGIC_WRAPPER_GUEST_ID);
+}
+
+#[ignore]
+#[test]
+fn print_logic_wrapper_elf_id() {
+    // Write the elf binary to a file
+    std::fs::write(
+        "../../aarm/elfs/logic_wrapper_elf.bin",
+        LOGIC_WRAPPER_GUEST_ELF,
+    )
+    .expect("Failed to write logic wrapper guest ELF binary");
+
+    // Print the ID
+    println!("Logic Wrapper Guest ID: {:?}", LOGIC_WRAPPER_GUEST_ID);
+}
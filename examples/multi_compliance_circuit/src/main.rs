@@ -0,0 +1,89 @@
+use aarm_core::{
+    commitment_tree::CommitmentTree,
+    compliance::{
+        ComplianceWitness, ConsumedResourceWitness, MultiComplianceInstance, MultiComplianceWitness,
+    },
+    constants::COMMITMENT_TREE_DEPTH,
+};
+use multi_compliance_methods::{MULTI_COMPLIANCE_GUEST_ELF, MULTI_COMPLIANCE_GUEST_ID};
+use risc0_zkvm::{default_prover, ExecutorEnv};
+use std::time::Instant;
+
+pub fn main() {
+    let prove_start_timer = Instant::now();
+
+    // Two distinct consumed and two created resources in a single unit
+    let single_witness = ComplianceWitness::<COMMITMENT_TREE_DEPTH>::default();
+    let first_consumed = single_witness.consumed_resource;
+    let mut second_consumed = first_consumed;
+    second_consumed.nonce[0] = 1;
+    let commitment_tree = CommitmentTree::<COMMITMENT_TREE_DEPTH>::from_leaves(&[
+        first_consumed.commitment(),
+        second_consumed.commitment(),
+    ])
+    .unwrap();
+    let consumed = [first_consumed, second_consumed]
+        .into_iter()
+        .enumerate()
+        .map(|(position, resource)| {
+            ConsumedResourceWitness::new(
+                resource,
+                single_witness.nf_key,
+                commitment_tree.path(position).unwrap(),
+            )
+        })
+        .collect();
+    let mut created = single_witness.created_resource;
+    created.nonce[0] = 1;
+    let compliance_witness = MultiComplianceWitness::<COMMITMENT_TREE_DEPTH>::from_resources(
+        consumed,
+        vec![single_witness.created_resource, created],
+    );
+
+    let env = ExecutorEnv::builder()
+        .write(&compliance_witness)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let prover = default_prover();
+
+    // Produce a receipt by proving the specified ELF binary.
+    let receipt = prover
+        .prove(env, MULTI_COMPLIANCE_GUEST_ELF)
+        .unwrap()
+        .receipt;
+
+    let prove_duration = prove_start_timer.elapsed();
+    println!("Prove duration time: {:?}", prove_duration);
+
+    let extract_journal_start_timer = Instant::now();
+    // Extract journal of receipt
+    let _compliance_instance: MultiComplianceInstance = receipt.journal.decode().unwrap();
+
+    let extract_journal_duration = extract_journal_start_timer.elapsed();
+    println!(
+        "Extract Journal duration time: {:?}",
+        extract_journal_duration
+    );
+
+    let verify_start_timer = Instant::now();
+
+    receipt.verify(MULTI_COMPLIANCE_GUEST_ID).unwrap();
+    let verify_duration = verify_start_timer.elapsed();
+    println!("Verify duration time: {:?}", verify_duration);
+}
+
+#[ignore]
+#[test]
+fn print_multi_compliance_elf_id() {
+    // Write the elf binary to a file
+    std::fs::write(
+        "../../aarm/elfs/multi_compliance_elf.bin",
+        MULTI_COMPLIANCE_GUEST_ELF,
+    )
+    .expect("Failed to write multi compliance guest ELF binary");
+
+    // Print the ID
+    println!("Multi Compliance Guest ID: {:?}", MULTI_COMPLIANCE_GUEST_ID);
+}
@@ -0,0 +1,64 @@
+use aarm_core::{
+    compliance::{ComplianceWitness, PrivateComplianceInstance, PrivateComplianceWitness},
+    constants::COMMITMENT_TREE_DEPTH,
+};
+use private_compliance_methods::{PRIVATE_COMPLIANCE_GUEST_ELF, PRIVATE_COMPLIANCE_GUEST_ID};
+use risc0_zkvm::{default_prover, ExecutorEnv};
+use std::time::Instant;
+
+pub fn main() {
+    let prove_start_timer = Instant::now();
+
+    let compliance_witness =
+        PrivateComplianceWitness::new(ComplianceWitness::<COMMITMENT_TREE_DEPTH>::default());
+
+    let env = ExecutorEnv::builder()
+        .write(&compliance_witness)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let prover = default_prover();
+
+    // Produce a receipt by proving the specified ELF binary.
+    let receipt = prover
+        .prove(env, PRIVATE_COMPLIANCE_GUEST_ELF)
+        .unwrap()
+        .receipt;
+
+    let prove_duration = prove_start_timer.elapsed();
+    println!("Prove duration time: {:?}", prove_duration);
+
+    let extract_journal_start_timer = Instant::now();
+    // Extract journal of receipt
+    let _compliance_instance: PrivateComplianceInstance = receipt.journal.decode().unwrap();
+
+    let extract_journal_duration = extract_journal_start_timer.elapsed();
+    println!(
+        "Extract Journal duration time: {:?}",
+        extract_journal_duration
+    );
+
+    let verify_start_timer = Instant::now();
+
+    receipt.verify(PRIVATE_COMPLIANCE_GUEST_ID).unwrap();
+    let verify_duration = verify_start_timer.elapsed();
+    println!("Verify duration time: {:?}", verify_duration);
+}
+
+#[ignore]
+#[test]
+fn print_private_compliance_elf_id() {
+    // Write the elf binary to a file
+    std::fs::write(
+        "../../aarm/elfs/private_compliance_elf.bin",
+        PRIVATE_COMPLIANCE_GUEST_ELF,
+    )
+    .expect("Failed to write private compliance guest ELF binary");
+
+    // Print the ID
+    println!(
+        "Private Compliance Guest ID: {:?}",
+        PRIVATE_COMPLIANCE_GUEST_ID
+    );
+}